@@ -0,0 +1,71 @@
+use std::hint::black_box;
+use std::time::Instant;
+
+use engine::prelude::*;
+
+const ROUNDS: usize = 1 << 18;
+
+const FENS: [&str; 4] = [
+    Board::BRANDUBH_FEN,
+    Board::TABLUT_FEN,
+    "9/3K5/9/9/9/9/9/9/9 B",
+    Board::HNEFATAFL_FEN,
+];
+
+struct ScanIter<'a> {
+    counter: usize,
+    bitboard: &'a Bitboard,
+}
+
+impl<'a> Iterator for ScanIter<'a> {
+    type Item = (Piece, Square);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let geometry = self.bitboard.geometry();
+        let mut square = geometry.square(self.counter).ok()?;
+        let mut piece = None;
+
+        while piece.is_none() {
+            piece = Piece::ALL.into_iter().find(|&p| (self.bitboard[p] & Mask::bit(geometry.index(square))) > Mask(0));
+
+            if piece.is_none() {
+                self.counter += 1;
+                square = geometry.square(self.counter).ok()?;
+            }
+        }
+
+        self.counter += 1;
+        Some((piece?, square))
+    }
+}
+
+fn bench<'a, I>(label: &str, bitboards: &'a [Bitboard], iter: impl Fn(&'a Bitboard) -> I) -> usize
+where
+    I: Iterator<Item = (Piece, Square)>,
+{
+    let start = Instant::now();
+    let mut checksum = 0;
+    for _ in 0..ROUNDS {
+        for bitboard in black_box(bitboards) {
+            checksum += iter(bitboard).map(|(piece, square)| piece as usize + square.row as usize).sum::<usize>();
+        }
+    }
+
+    black_box(checksum);
+    let elapsed = start.elapsed();
+    let iterations = ROUNDS * bitboards.len();
+    println!(
+        "{label}: {iterations} iterations in {elapsed:?} ({:.2} ns/iteration)",
+        elapsed.as_nanos() as f64 / iterations as f64
+    );
+
+    checksum
+}
+
+fn main() {
+    let bitboards: Vec<Bitboard> = FENS.iter().map(|fen| *Board::from_fen(fen).unwrap().bitboard()).collect();
+
+    let scan = bench("scan", &bitboards, |bitboard| ScanIter { counter: 0, bitboard });
+    let bitboard_iter = bench("BitboardIter", &bitboards, Bitboard::iter);
+    assert_eq!(scan, bitboard_iter);
+}
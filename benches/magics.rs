@@ -0,0 +1,46 @@
+use std::hint::black_box;
+use std::time::Instant;
+
+use engine::prelude::*;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+const LOOKUPS: usize = 1 << 22;
+
+fn bench(label: &str, samples: &[(Square, Mask)], lookup: impl Fn(Square, Mask) -> Mask) {
+    let start = Instant::now();
+    let mut checksum = Mask(0);
+    for &(square, blockers) in samples {
+        checksum |= lookup(square, black_box(blockers));
+    }
+
+    black_box(checksum);
+    let elapsed = start.elapsed();
+    println!(
+        "{label}: {LOOKUPS} lookups in {elapsed:?} ({:.2} ns/lookup)",
+        elapsed.as_nanos() as f64 / LOOKUPS as f64
+    );
+}
+
+fn main() {
+    let geometry = Geometry::TABLUT;
+    let Some(table) = MagicTable::embedded(geometry) else {
+        panic!("No magic table is embedded for {geometry:?}.");
+    };
+    let hash_table = HashMagicTable::from(table);
+    let layout = geometry.layout::<Mask>().unwrap();
+
+    let mut rng = StdRng::seed_from_u64(MagicTable::DEFAULT_SEED);
+    let samples: Vec<(Square, Mask)> = (0..LOOKUPS)
+        .map(|_| {
+            let square = geometry.square(rng.random_range(0..geometry.total_squares())).unwrap();
+            let occupancy = rng.random::<u128>() & rng.random::<u128>();
+            (square, Mask(occupancy) & layout.moves(square))
+        })
+        .collect();
+
+    bench("legal_moves", &samples, |square, blockers| layout.legal_moves(square, blockers));
+    bench("HashMagicTable", &samples, |square, blockers| hash_table.moves(square, blockers));
+    bench("MagicTable", &samples, |square, blockers| table.moves(square, blockers));
+}
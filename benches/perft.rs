@@ -0,0 +1,26 @@
+use std::time::Instant;
+
+use engine::prelude::*;
+
+const DEPTH: usize = 4;
+
+fn bench(label: &str, magic_table: Option<&MagicTable>) -> u64 {
+    let mut board = Board::new();
+
+    let start = Instant::now();
+    let nodes = board.perft(DEPTH, magic_table);
+    let elapsed = start.elapsed();
+
+    println!(
+        "perft({DEPTH}) {label}: {nodes} nodes in {elapsed:?} ({:.0} nodes/s)",
+        nodes as f64 / elapsed.as_secs_f64()
+    );
+
+    nodes
+}
+
+fn main() {
+    let legal_moves = bench("legal_moves", None);
+    let magics = bench("magics", MagicTable::embedded(Geometry::TABLUT));
+    assert_eq!(legal_moves, magics);
+}
@@ -0,0 +1,176 @@
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+const MAGICS: [(usize, &str); 2] = [(7, "assets/brandubh_magics.ron"), (9, "assets/magics.ron")];
+
+#[derive(Deserialize)]
+struct MagicNumbers {
+    magics: Vec<String>,
+}
+
+struct Layout {
+    length: usize,
+}
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is not set.");
+    let big_endian = env::var("CARGO_CFG_TARGET_ENDIAN").is_ok_and(|endian| endian == "big");
+    let mut source = String::new();
+    let mut tables = Vec::with_capacity(MAGICS.len());
+
+    for (length, path) in MAGICS {
+        println!("cargo:rerun-if-changed={path}");
+        let moves = Path::new(&out_dir).join(format!("magic_moves_{length}.bin"));
+        tables.push(embed(&mut source, Layout { length }, path, &moves, big_endian));
+    }
+
+    writeln!(source, "pub(crate) static EMBEDDED_TABLES: [EmbeddedTable; {}] = [", tables.len()).expect("Failed to format the embedded magic tables.");
+    for table in tables {
+        writeln!(source, "    {table},").expect("Failed to format the embedded magic tables.");
+    }
+
+    source.push_str("];\n");
+
+    fs::write(Path::new(&out_dir).join("magics.rs"), source).expect("Failed to write the embedded magic tables.");
+}
+
+fn embed(source: &mut String, layout: Layout, path: &str, moves_path: &Path, big_endian: bool) -> String {
+    let total_squares = layout.length * layout.length;
+    let ron = fs::read_to_string(path).expect("Failed to read the magics file.");
+    let numbers: MagicNumbers = ron::from_str(&ron).expect("Failed to parse the magics file.");
+    if numbers.magics.len() != total_squares {
+        panic!("Expected {total_squares} magics in {path}, found {}.", numbers.magics.len());
+    }
+
+    let magics: Vec<u128> = numbers
+        .magics
+        .iter()
+        .map(|magic| magic.parse().expect("Magic is not an unsigned 128-bit integer."))
+        .collect();
+
+    let mut blockers = Vec::with_capacity(total_squares);
+    let mut shifts = Vec::with_capacity(total_squares);
+    let mut offsets = Vec::with_capacity(total_squares);
+    let mut moves = Vec::new();
+
+    for (index, &magic) in magics.iter().enumerate() {
+        let relevant = layout.relevant_blockers(index);
+        let shift = relevant.count_ones();
+        let offset = moves.len();
+        let mut filled = vec![false; 1 << shift];
+
+        moves.resize(offset + (1 << shift), 0);
+        for subset in subsets(relevant) {
+            let key = (subset.wrapping_mul(magic) >> (128 - shift)) as usize;
+            let legal_moves = layout.legal_moves(index, subset);
+
+            if filled[key] && moves[offset + key] != legal_moves {
+                panic!("Magic of square {index} in {path} has a destructive collision.");
+            }
+
+            filled[key] = true;
+            moves[offset + key] = legal_moves;
+        }
+
+        blockers.push(relevant);
+        shifts.push(shift);
+        offsets.push(offset);
+    }
+
+    let prefix = format!("EMBEDDED_{}", layout.length);
+    let bytes: Vec<u8> = moves
+        .iter()
+        .flat_map(|mask| match big_endian {
+            true => mask.to_be_bytes(),
+            false => mask.to_le_bytes(),
+        })
+        .collect();
+    fs::write(moves_path, bytes).expect("Failed to write the embedded magic moves.");
+
+    write_array(source, &format!("static {prefix}_MAGICS"), "Mask", &magics, |magic| format!("Mask({magic:#034x})"));
+    write_array(source, &format!("static {prefix}_BLOCKERS"), "Mask", &blockers, |mask| format!("Mask({mask:#034x})"));
+    write_array(source, &format!("static {prefix}_SHIFTS"), "u32", &shifts, u32::to_string);
+    write_array(source, &format!("static {prefix}_OFFSETS"), "usize", &offsets, usize::to_string);
+    writeln!(source, "static {prefix}_MOVES: &Aligned<[u8]> = &Aligned(*include_bytes!({moves_path:?}));").expect("Failed to format the embedded magic tables.");
+
+    format!(
+        "EmbeddedTable {{ length: {}, magics: &{prefix}_MAGICS, blockers: &{prefix}_BLOCKERS, shifts: &{prefix}_SHIFTS, offsets: &{prefix}_OFFSETS, moves: {prefix}_MOVES }}",
+        layout.length
+    )
+}
+
+fn write_array<T>(source: &mut String, item: &str, ty: &str, values: &[T], format: impl Fn(&T) -> String) {
+    writeln!(source, "{item}: [{ty}; {}] = [", values.len()).expect("Failed to format the embedded magic tables.");
+    for value in values {
+        writeln!(source, "    {},", format(value)).expect("Failed to format the embedded magic tables.");
+    }
+
+    source.push_str("];\n");
+}
+
+impl Layout {
+    fn rank(&self, row: usize) -> u128 {
+        ((1 << self.length) - 1) << (row * self.length)
+    }
+
+    fn file(&self, col: usize) -> u128 {
+        (0..self.length).fold(0, |file, row| file | 1 << (row * self.length + col))
+    }
+
+    fn relevant_blockers(&self, index: usize) -> u128 {
+        let length = self.length;
+        let (row, col) = (index / length, index % length);
+        let last = length - 1;
+        let square = 1 << index;
+        let corners = 1 | 1 << last | 1 << (last * length) | 1 << (length * length - 1);
+        let edges = self.rank(0) | self.rank(last) | self.file(0) | self.file(last);
+        let mut relevant = (self.rank(row) | self.file(col)) & !square & !edges;
+
+        if col == 0 || col == last {
+            relevant |= self.file(col) & !corners & !square;
+        }
+
+        if row == 0 || row == last {
+            relevant |= self.rank(row) & !corners & !square;
+        }
+
+        relevant
+    }
+
+    fn legal_moves(&self, index: usize, blockers: u128) -> u128 {
+        let length = self.length;
+        let (row, col) = (index / length, index % length);
+        let step = length as isize;
+        let rays = [(step, length - 1 - row), (-step, row), (1, length - 1 - col), (-1, col)];
+        let mut legal_moves = 0;
+
+        for (step, distance) in rays {
+            let mut target = index as isize;
+            for _ in 0..distance {
+                target += step;
+                if (blockers >> target) & 1 == 1 {
+                    break;
+                }
+
+                legal_moves |= 1 << target;
+            }
+        }
+
+        legal_moves
+    }
+}
+
+fn subsets(mask: u128) -> impl Iterator<Item = u128> {
+    let mut subset = Some(0u128);
+
+    std::iter::from_fn(move || {
+        let current = subset?;
+        let next = current.wrapping_sub(mask) & mask;
+        subset = (next != 0).then_some(next);
+        Some(current)
+    })
+}
@@ -1,9 +1,13 @@
-use crate::mask::Mask;
+use std::ops::Deref;
+
+use crate::bit_set::BitSet;
+use crate::bitboard::GenericBitboard;
+use crate::geometry::Geometry;
 use crate::piece::Piece;
-use crate::prelude::Bitboard;
 use crate::square::Square;
+use crate::symmetry::Symmetry;
 
-#[derive(Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Action {
     pub piece: Piece,
     pub from: Square,
@@ -19,11 +23,75 @@ impl Action {
         }
     }
 
-    pub fn valid(&self, bitboard: &Bitboard) -> bool {
-        bitboard[self.piece] & self.from.mask() > Mask(0)
+    pub fn valid<M: BitSet>(&self, bitboard: &GenericBitboard<M>) -> bool {
+        self.piece < Piece::Length && bitboard[self.piece].get(bitboard.geometry().index(self.from))
     }
     
-    pub fn turn_valid(&self, turn_mask: Mask) -> bool {
-        self.from.mask() & turn_mask > Mask(0)
+    pub fn turn_valid<M: BitSet>(&self, turn_mask: M, geometry: Geometry) -> bool {
+        turn_mask.get(geometry.index(self.from))
+    }
+
+    pub fn transformed(&self, symmetry: Symmetry, geometry: Geometry) -> Action {
+        Action::new(
+            self.piece,
+            geometry.transform_square(self.from, symmetry),
+            geometry.transform_square(self.to, symmetry),
+        )
+    }
+}
+
+pub type WideActionList = ActionList<{ 4 * Geometry::MAX_SQUARES }>;
+
+pub struct ActionList<const CAPACITY: usize = 512> {
+    actions: [Action; CAPACITY],
+    len: usize,
+}
+
+impl<const CAPACITY: usize> ActionList<CAPACITY> {
+    pub const CAPACITY: usize = CAPACITY;
+
+    pub fn new() -> Self {
+        const EMPTY: Action = Action {
+            piece: Piece::Attacker,
+            from: Square { row: 0, col: 0 },
+            to: Square { row: 0, col: 0 },
+        };
+
+        Self {
+            actions: [EMPTY; CAPACITY],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, action: Action) {
+        self.actions[self.len] = action;
+        self.len += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const CAPACITY: usize> Default for ActionList<CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const CAPACITY: usize> Deref for ActionList<CAPACITY> {
+    type Target = [Action];
+
+    fn deref(&self) -> &Self::Target {
+        &self.actions[..self.len]
+    }
+}
+
+impl<'a, const CAPACITY: usize> IntoIterator for &'a ActionList<CAPACITY> {
+    type Item = &'a Action;
+    type IntoIter = std::slice::Iter<'a, Action>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
     }
 }
@@ -0,0 +1,24 @@
+use std::env;
+use std::error::Error;
+
+use engine::prelude::*;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| MagicTable::MAGICS_PATH.to_string());
+    let seed = match args.next() {
+        Some(seed) => seed.parse()?,
+        None => MagicTable::DEFAULT_SEED,
+    };
+
+    let geometry = match args.next() {
+        Some(length) => Geometry::new(length.parse()?)?,
+        None => Geometry::TABLUT,
+    };
+
+    let table = MagicTable::generate_with_geometry(geometry, seed)?;
+    table.save(&path)?;
+
+    println!("Wrote magics for seed {seed} to {path}.");
+    Ok(())
+}
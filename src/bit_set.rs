@@ -0,0 +1,64 @@
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::ops::BitAnd;
+use std::ops::BitAndAssign;
+use std::ops::BitOr;
+use std::ops::BitOrAssign;
+use std::ops::Not;
+
+use crate::geometry::Geometry;
+use crate::magics::MagicTable;
+use crate::mask::MaskDisplay;
+use crate::mask::MaskIter;
+use crate::square::Square;
+
+pub trait BitSet:
+    'static
+    + Send
+    + Sync
+    + Copy
+    + Eq
+    + Hash
+    + Debug
+    + Display
+    + Default
+    + BitAnd<Output = Self>
+    + BitAndAssign
+    + BitOr<Output = Self>
+    + BitOrAssign
+    + Not<Output = Self>
+{
+    const BITS: usize;
+    const EMPTY: Self;
+
+    fn bit(index: usize) -> Self;
+
+    fn low_bits(count: usize) -> Self;
+
+    fn get(self, index: usize) -> bool;
+
+    fn shift_left(self, bits: usize) -> Self;
+
+    fn shift_right(self, bits: usize) -> Self;
+
+    fn is_empty(self) -> bool;
+
+    fn count(self) -> u32;
+
+    fn lsb(self) -> Option<usize>;
+
+    fn pop_lsb(&mut self) -> Option<usize>;
+
+    fn magic_moves(_magic_table: &MagicTable, _square: Square, _occupied: Self) -> Option<Self> {
+        None
+    }
+
+    fn iter_squares(self, geometry: Geometry) -> MaskIter<Self> {
+        MaskIter::new(self & Self::low_bits(geometry.total_squares()), geometry)
+    }
+
+    fn display(self, geometry: Geometry) -> MaskDisplay<Self> {
+        MaskDisplay::new(self, geometry)
+    }
+}
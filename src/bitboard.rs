@@ -5,205 +5,156 @@ use std::fmt::Formatter;
 use std::ops::Index;
 use std::ops::IndexMut;
 
+use crate::bit_set::BitSet;
+use crate::fen;
+use crate::fen::FenError;
+use crate::geometry::Geometry;
+use crate::geometry::Layout;
 use crate::mask::Mask;
-use crate::VikingChessResult;
 use crate::piece::Piece;
 use crate::square::Square;
+use crate::symmetry::Symmetry;
 
-#[derive(Default)]
-pub struct Bitboard([Mask; Piece::Length as usize]);
-
-impl Bitboard {
-    pub const BOARD_LENGTH: usize = 9;
-    pub const TOTAL_SQUARES: usize = Bitboard::BOARD_LENGTH * Bitboard::BOARD_LENGTH;
-
-
-    pub fn from_fen(str: &'static str) -> VikingChessResult<Self> {
-        let mut bitboard = Self::default();
-        let mut col = 0;
-        let mut row = 0;
-
-        const BOARD_LENGTH: u8 = Bitboard::BOARD_LENGTH as u8;
-        for ch in str.chars() {
-            if matches!(ch, 'A' | 'D' | 'K') {
-                bitboard[Piece::from(ch)] |= Square::try_from((col, row))?.mask();
-                col += 1;
-            } else if let Some(digit) = ch.to_digit(10) {
-                col += digit as u8;
-            } else if (ch == '/' && col % BOARD_LENGTH != 0) || col > BOARD_LENGTH {
-                return Err(format!("Invalid notation {str}.").into());
-            } else if ch == '/' {
-                row += 1;
-                col = 0;
-            }
-        }
+pub type Bitboard = GenericBitboard<Mask>;
 
-        Ok(bitboard)
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub struct GenericBitboard<M: BitSet> {
+    masks: [M; Piece::Length as usize],
+    geometry: Geometry,
+}
+
+impl<M: BitSet> GenericBitboard<M> {
+    pub fn new(geometry: Geometry) -> Self {
+        Self {
+            masks: Default::default(),
+            geometry,
+        }
     }
 
-    pub fn iter<'a>(&'a self) -> BitboardIter<'a> {
-        BitboardIter::new(self)
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
     }
 
-    pub fn all(&self) -> Mask {
-        Piece::PIECES.into_iter().map(Piece::from).fold(Mask(0), |a, b| a | self[b])
+    pub fn from_fen(str: &str) -> Result<Self, FenError> {
+        let placement = fen::parse_placement(str, |length| Geometry::new(length).is_ok_and(|geometry| geometry.fits::<M>()))?;
+        let geometry = Geometry::new(placement.length).unwrap_or_default();
+        let mut bitboard = Self::new(geometry);
+
+        for (piece, row, col) in placement.pieces {
+            bitboard[piece] |= M::bit(geometry.index(Square::new(row as u8, col as u8)));
+        }
+
+        Ok(bitboard)
     }
 
-    pub fn moves(square: Square) -> Mask {
-        let square_col_mask = Mask(0x1008040201008040201u128 << square.col);
-        let square_row_mask = Mask(0x1ff << (9 * square.row));
+    pub fn to_fen(self) -> String {
+        fen::write_placement(self.geometry.length(), |row, col| {
+            let index = self.geometry.index(Square::new(row as u8, col as u8));
+            Piece::ALL.into_iter().find(|&piece| self[piece].get(index))
+        })
+    }
 
-        (square_col_mask | square_row_mask) & !square.mask()
+    pub fn iter<'a>(&'a self) -> BitboardIter<'a, M> {
+        BitboardIter::new(self)
     }
 
-    pub fn blockers(square: Square) -> Mask {
-        const COLUMNS: u128 = 0x1008040201008040201u128;
-        const ROWS: u128 = 0x1ff;
-        let cols = Mask(COLUMNS) | Mask(COLUMNS << 8);
-        let rows = Mask(ROWS) | Mask(ROWS << (9 * 8));
-        let corners = (1 << 0) | (1 << 8) | (1 << 72) | (1 << 80);
-        let mut potential_blockers = Self::moves(square) & !(cols | rows);
-
-        match (square.col, square.row) {
-            (0 | 8, 0 | 8) => {
-                potential_blockers |= Mask(COLUMNS & !corners);
-                potential_blockers |= Mask(ROWS & !corners);
-            },
-            (0 | 8, _) => {
-                potential_blockers |= Mask(COLUMNS & !corners & !square.mask().0);
-            },
-            (_, 0 | 8) => {
-                potential_blockers |= Mask(ROWS & !corners & !square.mask().0);
-            },
-            _ => {},
+    pub fn transformed(&self, symmetry: Symmetry, layout: &Layout<M>) -> Self {
+        let mut bitboard = Self::new(self.geometry);
+        for piece in Piece::ALL {
+            bitboard[piece] = layout.transform(self[piece], symmetry);
         }
 
-        potential_blockers
+        bitboard
     }
 
-    pub fn legal_moves(square: Square, blockers: Mask) -> Mask {
-        let mut legal_moves = Mask(0);
-        let rank = square.row;
-        let file = square.col;
+    pub fn all(&self) -> M {
+        Piece::ALL.into_iter().fold(M::EMPTY, |a, b| a | self[b])
+    }
 
-        let rank_into_square = |r: u8| Square::try_from((file, r)).unwrap();
-        let file_into_square = |f: u8| Square::try_from((f, rank)).unwrap();
-        let predicate = |s: &Square| (blockers & s.mask()).0 == 0;
-        let fold = |a: Mask, b: Square| a | b.mask();
+    fn write_grid(&self, f: &mut Formatter<'_>, square: impl Fn(usize) -> &'static str) -> fmt::Result {
+        let length = self.geometry.length();
+        for row in 0..length {
+            for col in 0..length {
+                write!(f, "{}", square(row * length + col))?;
+            }
 
-        legal_moves |= ((rank + 1)..9).map(rank_into_square).take_while(predicate).fold(Mask(0), fold);
-        legal_moves |= (0..rank).rev().map(rank_into_square).take_while(predicate).fold(Mask(0), fold);
-        legal_moves |= ((file + 1)..9).map(file_into_square).take_while(predicate).fold(Mask(0), fold);
-        legal_moves |= (0..file).rev().map(file_into_square).take_while(predicate).fold(Mask(0), fold);
+            writeln!(f)?;
+        }
 
-        legal_moves
+        Ok(())
     }
 }
 
-pub struct BitboardIter<'a> {
-    counter: usize,
-    bitboard: &'a Bitboard,
+pub struct BitboardIter<'a, M: BitSet = Mask> {
+    remaining: M,
+    bitboard: &'a GenericBitboard<M>,
 }
 
-impl<'a> BitboardIter<'a> {
-    pub fn new(bitboard: &'a Bitboard) -> Self {
-        Self { counter: 0, bitboard }
+impl<'a, M: BitSet> BitboardIter<'a, M> {
+    pub fn new(bitboard: &'a GenericBitboard<M>) -> Self {
+        Self {
+            remaining: bitboard.all() & M::low_bits(bitboard.geometry.total_squares()),
+            bitboard,
+        }
     }
 }
 
-impl<'a> Iterator for BitboardIter<'a> {
+impl<'a, M: BitSet> Iterator for BitboardIter<'a, M> {
     type Item = (Piece, Square);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut square = Square::try_from(self.counter).ok()?;
-        let mut piece = None;
-
-        while piece.is_none() {
-            let pieces = Piece::PIECES.map(Piece::from);
-
-            piece = pieces.into_iter().find(|&p| (self.bitboard[p] & square.mask()) > Mask(0));
-
-            if piece.is_none() {
-                self.counter += 1;
-                square = Square::try_from(self.counter).ok()?;
-            }
-        }
+        let index = self.remaining.pop_lsb()?;
+        let square = self.bitboard.geometry.square(index).ok()?;
+        let piece = Piece::ALL.into_iter().find(|&piece| self.bitboard[piece].get(index))?;
+        Some((piece, square))
+    }
 
-        self.counter += 1;
-        Some((piece?, square))
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.remaining.count() as usize;
+        (count, Some(count))
     }
 }
 
-impl Display for Bitboard {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        for i in 0..Bitboard::TOTAL_SQUARES as u128 {
-            let col = i % Bitboard::BOARD_LENGTH as u128;
-            let ch = match i {
-                i if (self[Piece::King].0 >> i) & 1 == 1 => "K",
-                i if (self[Piece::Defender].0 >> i) & 1 == 1 => "D",
-                i if (self[Piece::Attacker].0 >> i) & 1 == 1 => "A",
-                _ => ".",
-            };
-
-            write!(f, "{ch}")?;
-
-            if col + 1 == Bitboard::BOARD_LENGTH as u128 {
-                writeln!(f)?;
-            }
-        }
+impl<'a, M: BitSet> ExactSizeIterator for BitboardIter<'a, M> {}
 
-        Ok(())
+impl<M: BitSet> Display for GenericBitboard<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write_grid(f, |index| match index {
+            index if self[Piece::King].get(index) => "K",
+            index if self[Piece::Defender].get(index) => "D",
+            index if self[Piece::Attacker].get(index) => "A",
+            _ => ".",
+        })
     }
 }
 
-impl Index<Piece> for Bitboard {
-    type Output = Mask;
+impl<M: BitSet> Index<Piece> for GenericBitboard<M> {
+    type Output = M;
 
     fn index(&self, index: Piece) -> &Self::Output {
         if index >= Piece::Length {
             panic!("Cannot index {index:?} to the bitboard.");
         }
 
-        &self.0[index as usize]
+        &self.masks[index as usize]
     }
 }
 
-impl IndexMut<Piece> for Bitboard {
+impl<M: BitSet> IndexMut<Piece> for GenericBitboard<M> {
     fn index_mut(&mut self, index: Piece) -> &mut Self::Output {
         if index >= Piece::Length {
             panic!("Cannot index mut {index:?} to the bitboard.");
         }
-        &mut self.0[index as usize]
-    }
-}
-
-impl Display for Mask {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        for i in 0..Bitboard::TOTAL_SQUARES as u128 {
-            let col = i % Bitboard::BOARD_LENGTH as u128;
-
-            let mut ch = ".";
-            if (self.0 >> i) & 1 == 1 {
-                ch = "1";
-            }
-
-            write!(f, "{ch}")?;
-
-            if col + 1 == Bitboard::BOARD_LENGTH as u128 {
-                writeln!(f)?;
-            }
-        }
-
-        Ok(())
+        &mut self.masks[index as usize]
     }
 }
 
-impl Debug for Bitboard {
+impl<M: BitSet> Debug for GenericBitboard<M> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         writeln!(f, "Bitboard:\n{self}")?;
 
-        for piece in Piece::PIECES.into_iter().map(Piece::from) {
-            writeln!(f, "Mask {piece:?}:\n{}", self[piece])?;
+        for piece in Piece::ALL {
+            writeln!(f, "Mask {piece:?}:\n{}", self[piece].display(self.geometry))?;
         }
 
         Ok(())
@@ -1,23 +1,50 @@
 use std::fmt;
 use std::fmt::Display;
 use std::fmt::Formatter;
+use std::str::FromStr;
 
 use crate::action::Action;
+use crate::action::ActionList;
+use crate::action::WideActionList;
 use crate::square::Square;
+use crate::VikingChessError;
 use crate::VikingChessResult;
-use crate::bitboard::Bitboard;
+use crate::bit_set::BitSet;
 use crate::bitboard::BitboardIter;
+use crate::bitboard::GenericBitboard;
+use crate::error::Restriction;
+use crate::fen;
+use crate::fen::FenError;
+use crate::fen::FenErrorKind;
+use crate::geometry::Geometry;
+use crate::geometry::Layout;
 use crate::magics::MagicTable;
 use crate::mask::Mask;
+use crate::outcome::DrawReason;
+use crate::outcome::GameResult;
+use crate::outcome::WinReason;
 use crate::piece::Piece;
+use crate::rules::KingCapture;
+use crate::rules::Repetition;
+use crate::rules::RuleSet;
 use crate::state::State;
+use crate::symmetry::Symmetry;
+use crate::wide_mask::WideMask;
 use crate::zobrist::ZobristTable;
 
-pub struct Board {
-    bitboard: Bitboard,
-    zobrist_table: ZobristTable,
-    history: Vec<State>,
-    pub state: State,
+pub type Board = GenericBoard<Mask>;
+pub type WideBoard<const N: usize> = GenericBoard<WideMask<N>, { WideActionList::CAPACITY }>;
+pub type Board13 = WideBoard<3>;
+pub type Board19 = WideBoard<6>;
+
+pub struct GenericBoard<M: BitSet, const ACTIONS: usize = 512> {
+    bitboard: GenericBitboard<M>,
+    layout: &'static Layout<M>,
+    zobrist_table: &'static ZobristTable,
+    history: Vec<State<M>>,
+    pub state: State<M>,
+    rules: RuleSet,
+    move_limit: Option<usize>,
 }
 
 impl Default for Board {
@@ -27,129 +54,686 @@ impl Default for Board {
 }
 
 impl Board {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<M: BitSet, const ACTIONS: usize> GenericBoard<M, ACTIONS> {
     pub const STARTING_FEN: &'static str = "3AAA3/4A4/4D4/A3D3A/AADDKDDAA/A3D3A/4D4/4A4/3AAA3 B";
+    pub const BRANDUBH_FEN: &'static str = "3A3/3A3/3D3/AADKDAA/3D3/3A3/3A3 B";
+    pub const TABLUT_FEN: &'static str = Self::STARTING_FEN;
+    pub const TAWLBWRDD_FEN: &'static str = "4AAA4/4A1A4/5A5/5D5/AA2DDD2AA/A1ADDKDDA1A/AA2DDD2AA/5D5/5A5/4A1A4/4AAA4 B";
+    pub const HNEFATAFL_FEN: &'static str = "3AAAAA3/5A5/11/A4D4A/A3DDD3A/AA1DDKDD1AA/A3DDD3A/A4D4A/11/5A5/3AAAAA3 B";
     pub const EMPTY_FEN: &'static str = "9/9/9/9/9/9/9/9/9 B";
+    pub const REPETITION_LIMIT: usize = 3;
 
-    pub fn new() -> Self {
-        Self::default()
+    pub fn from_fen(str: &str) -> VikingChessResult<Self> {
+        Self::from_fen_with_rules(str, RuleSet::default())
     }
 
-    pub fn from_fen(str: &'static str) -> VikingChessResult<Self> {
-        let mut fen_iter = str.split(" ");
-        let bitboard = Bitboard::from_fen(fen_iter.next().expect("Invalid FEN; No state specified."))?;
-        let zobrist_table = ZobristTable::new();
-        let initial_hash = Board::calculate_hash(&bitboard, &zobrist_table);
-        let turn = match fen_iter.next() {
-            Some("B") => Piece::Attacker,
-            Some("W") => Piece::Defender,
-            x => panic!("Invalid FEN; Current turn is not specified. {x:?}"),
+    pub fn from_fen_with_rules(str: &str, rules: RuleSet) -> VikingChessResult<Self> {
+        let mut fen_iter = str.split_whitespace();
+        let Some(placement) = fen_iter.next() else {
+            return Err(FenError::new(FenErrorKind::Empty, 0, 0).into());
         };
 
+        let bitboard = GenericBitboard::<M>::from_fen(placement)?;
+        let geometry = bitboard.geometry();
+        let layout = geometry.layout::<M>()?;
+        let length = geometry.length();
+        let turn = fen::parse_turn(fen_iter, length)?;
+
+        for piece in Piece::ALL {
+            let mut restricted = rules.restricted(piece, layout);
+            if piece != Piece::King {
+                restricted |= layout.throne();
+            }
+            let restricted = bitboard[piece] & restricted;
+            if let Some(index) = restricted.lsb() {
+                let kind = FenErrorKind::RestrictedSquare(piece);
+                return Err(FenError::new(kind, index / length, index % length).into());
+            }
+        }
+
         let state = State {
-            zobrist_hash: initial_hash,
+            zobrist_hash: 0,
             turn,
             action: None,
+            captured: GenericBitboard::new(geometry),
         };
 
-        let history = vec![state];
-
-        Ok(Self {
+        let mut board = Self {
             bitboard,
-            zobrist_table,
+            layout,
+            zobrist_table: ZobristTable::shared(),
             state,
-            history,
-        })
+            history: Vec::new(),
+            rules,
+            move_limit: None,
+        };
+
+        board.state.zobrist_hash = board.calculate_hash();
+        board.history.push(board.state);
+
+        Ok(board)
     }
 
-    pub fn iter_bitboard<'a>(&'a self) -> BitboardIter<'a> {
+    pub fn to_fen(&self) -> String {
+        let turn = match self.state.turn {
+            Piece::Attacker => "B",
+            _ => "W",
+        };
+
+        format!("{} {turn}", self.bitboard.to_fen())
+    }
+
+    pub fn transformed(&self, symmetry: Symmetry) -> Self {
+        let geometry = self.geometry();
+        let state = State {
+            zobrist_hash: 0,
+            turn: self.state.turn,
+            action: None,
+            captured: GenericBitboard::new(geometry),
+        };
+
+        let mut board = Self {
+            bitboard: self.bitboard.transformed(symmetry, self.layout),
+            layout: self.layout,
+            zobrist_table: self.zobrist_table,
+            state,
+            history: Vec::new(),
+            rules: self.rules,
+            move_limit: self.move_limit,
+        };
+
+        board.state.zobrist_hash = board.calculate_hash();
+        board.history.push(board.state);
+        board
+    }
+
+    pub fn canonical(&self) -> (Self, Symmetry) {
+        Symmetry::ALL
+            .into_iter()
+            .map(|symmetry| (self.transformed(symmetry), symmetry))
+            .min_by_key(|(board, _)| board.state.zobrist_hash)
+            .expect("There is always at least one symmetry.")
+    }
+
+    pub fn bitboard(&self) -> &GenericBitboard<M> {
+        &self.bitboard
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        self.bitboard.geometry()
+    }
+
+    pub fn layout(&self) -> &'static Layout<M> {
+        self.layout
+    }
+
+    pub fn repetitions(&self) -> usize {
+        let (current, previous) = self.history.split_last().expect("The history is empty.");
+        previous.iter().filter(|state| state.zobrist_hash == current.zobrist_hash).count()
+    }
+
+    pub fn move_count(&self) -> usize {
+        self.history.len() - 1
+    }
+
+    pub fn move_limit(&self) -> Option<usize> {
+        self.move_limit
+    }
+
+    pub fn set_move_limit(&mut self, move_limit: Option<usize>) {
+        self.move_limit = move_limit;
+    }
+
+    pub fn iter_bitboard<'a>(&'a self) -> BitboardIter<'a, M> {
         self.bitboard.iter()
     }
 
-    pub fn turn_mask(&self) -> Mask {
+    pub fn turn_mask(&self) -> VikingChessResult<M> {
         match self.state.turn {
-            Piece::Attacker => self.bitboard[Piece::Attacker],
-            Piece::Defender => self.bitboard[Piece::Defender] | self.bitboard[Piece::King],
-            _ => panic!("Invalid current turn."),
+            Piece::Attacker => Ok(self.bitboard[Piece::Attacker]),
+            Piece::Defender => Ok(self.bitboard[Piece::Defender] | self.bitboard[Piece::King]),
+            turn => Err(VikingChessError::InvalidTurn { turn }),
         }
     }
 
-    fn calculate_hash(bitboard: &Bitboard, zobrist_table: &ZobristTable) -> u64 {
-        let mut hash = 0;
-        for (piece, square) in bitboard.iter() {
-            hash ^= zobrist_table[(piece, square)];
+    pub fn calculate_hash(&self) -> u64 {
+        let mut hash = self.zobrist_table.turn_key(self.state.turn);
+        hash ^= self.zobrist_table.rules_key(&self.rules);
+        hash ^= self.zobrist_table.geometry_key(self.geometry());
+
+        for (piece, square) in self.bitboard.iter() {
+            hash ^= self.zobrist_table.piece_key(self.geometry(), piece, square);
         }
 
         hash
     }
 
-    fn moves(&self, square: Square, magic_table: Option<&MagicTable>) -> Mask {
-        let blockers = Bitboard::moves(square) & self.bitboard.all();
-        match magic_table {
-            Some(magic_table) => {
-                let blockers = blockers & Bitboard::blockers(square);
-                let square_index = square.index();
-                let magic = magic_table.magics[square_index];
-                let shift = MagicTable::SHIFTS[square_index];
-                let index = Mask(blockers.wrapping_mul(magic.0) >> (128 - shift));
-                magic_table.moves[square_index][&index] & !self.bitboard.all()
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    pub fn set_rules(&mut self, rules: RuleSet) {
+        let key = self.zobrist_table.rules_key(&self.rules) ^ self.zobrist_table.rules_key(&rules);
+        for state in self.history.iter_mut() {
+            state.zobrist_hash ^= key;
+        }
+
+        self.state.zobrist_hash ^= key;
+        self.rules = rules;
+    }
+
+    pub fn king_capture(&self) -> KingCapture {
+        self.rules.king_capture
+    }
+
+    pub fn set_king_capture(&mut self, king_capture: KingCapture) {
+        self.set_rules(RuleSet {
+            king_capture,
+            ..self.rules
+        });
+    }
+
+    pub fn set_turn(&mut self, turn: Piece) {
+        let key = self.zobrist_table.turn_key(self.state.turn) ^ self.zobrist_table.turn_key(turn);
+        self.state.zobrist_hash ^= key;
+        self.state.turn = turn;
+
+        if let Some(last) = self.history.last_mut() {
+            *last = self.state;
+        }
+    }
+
+    fn moves(&self, square: Square, magic_table: Option<&MagicTable>) -> M {
+        let mut occupied = self.bitboard.all();
+        if !self.rules.throne_passable {
+            occupied |= self.layout.throne();
+        }
+
+        self.slide(square, occupied, magic_table)
+    }
+
+    fn slide(&self, square: Square, occupied: M, magic_table: Option<&MagicTable>) -> M {
+        let layout = self.layout;
+        let blockers = layout.moves(square) & occupied;
+        let magic_table = magic_table.filter(|magic_table| magic_table.geometry == layout.geometry());
+        match magic_table.and_then(|magic_table| M::magic_moves(magic_table, square, blockers)) {
+            Some(moves) => moves & !occupied,
+            None => layout.legal_moves(square, blockers),
+        }
+    }
+
+    fn captures(&self, action: Action) -> GenericBitboard<M> {
+        let layout = self.layout;
+        let geometry = layout.geometry();
+        let mut captured = GenericBitboard::new(geometry);
+        let (allies, enemy, hostile_throne) = match action.piece {
+            Piece::Attacker => (self.bitboard[Piece::Attacker], Piece::Defender, self.rules.throne_hostile_to_defenders),
+            _ if self.rules.armed_king => (self.bitboard[Piece::Defender] | self.bitboard[Piece::King], Piece::Attacker, true),
+            _ => (self.bitboard[Piece::Defender], Piece::Attacker, true),
+        };
+
+        let mut hostile = allies | self.rules.hostile_corners(layout);
+        if hostile_throne {
+            hostile |= layout.throne() & !self.bitboard.all();
+        }
+
+        let anvils = layout.interjacent(action.to) & hostile;
+        let mut victims = match action.piece == Piece::King && !self.rules.armed_king {
+            true => M::EMPTY,
+            false => layout.adjacent(action.to) & self.bitboard[enemy],
+        };
+
+        while let Some(victim) = victims.pop_lsb() {
+            let anvil = 2 * victim as i32 - geometry.index(action.to) as i32;
+            if anvil >= 0 && anvils.get(anvil as usize) {
+                captured[enemy] |= M::bit(victim);
+            }
+        }
+
+        if self.rules.shieldwall {
+            captured[enemy] |= self.shieldwall_captures(action, allies, enemy);
+        }
+
+        if self.king_captured(action) {
+            captured[Piece::King] = self.bitboard[Piece::King];
+        }
+
+        captured
+    }
+
+    fn shieldwall_captures(&self, action: Action, allies: M, enemy: Piece) -> M {
+        let layout = self.layout;
+        let geometry = layout.geometry();
+        let length = geometry.length() as u8;
+        let last = length - 1;
+        let edges = |side: usize, i: u8| match side {
+            0 => (Square::new(0, i), Square::new(1, i)),
+            1 => (Square::new(last, i), Square::new(last - 1, i)),
+            2 => (Square::new(i, 0), Square::new(i, 1)),
+            _ => (Square::new(i, last), Square::new(i, last - 1)),
+        };
+
+        let wall = match enemy {
+            Piece::Defender => self.bitboard[Piece::Defender] | self.bitboard[Piece::King],
+            _ => self.bitboard[enemy],
+        };
+
+        let brackets = allies | self.rules.hostile_corners(layout);
+        let mut captured = M::EMPTY;
+
+        for side in 0..4 {
+            let edge = |i: u8| edges(side, i);
+            let mut i = 0;
+            while i < length {
+                let start = i;
+                let mut row = M::EMPTY;
+                let mut front = M::EMPTY;
+
+                while i < length && wall.get(geometry.index(edge(i).0)) {
+                    row |= layout.mask(edge(i).0);
+                    front |= layout.mask(edge(i).1);
+                    i += 1;
+                }
+
+                if i == start {
+                    i += 1;
+                    continue;
+                } else if row.count() < 2 || start == 0 || i == length {
+                    continue;
+                }
+
+                let ends = layout.mask(edge(start - 1).0) | layout.mask(edge(i).0);
+                let completed = (ends | front).get(geometry.index(action.to));
+                if completed && (front & !allies).is_empty() && (ends & !brackets).is_empty() {
+                    captured |= row & self.bitboard[enemy];
+                }
             }
-            None => Bitboard::legal_moves(square, blockers),
         }
+
+        captured
     }
 
-    fn toggle_turn(&mut self) {
-        self.state.turn = match self.state.turn {
-            Piece::Attacker => Piece::Defender,
-            Piece::Defender => Piece::Attacker,
-            _ => panic!("Invalid current turn."),
+    fn king_captured(&self, action: Action) -> bool {
+        let layout = self.layout;
+        let geometry = layout.geometry();
+        let king = self.bitboard[Piece::King];
+        if action.piece != Piece::Attacker || (layout.adjacent(action.to) & king).is_empty() {
+            return false;
+        }
+
+        let Some(square) = king.iter_squares(geometry).next() else {
+            return false;
+        };
+
+        let hostile = self.bitboard[Piece::Attacker] | self.rules.hostile_corners(layout) | (layout.throne() & !self.bitboard.all());
+        let neighbours = layout.adjacent(square);
+        let near_throne = !((neighbours | king) & layout.throne()).is_empty();
+
+        match self.rules.king_capture {
+            KingCapture::Custodial if !near_throne => {
+                let anvil = 2 * geometry.index(square) as i32 - geometry.index(action.to) as i32;
+                anvil >= 0 && (neighbours & hostile).get(anvil as usize)
+            }
+            _ => {
+                let surrounded = (neighbours & !hostile).is_empty();
+                let on_edge = neighbours.count() < 4;
+                surrounded && (!on_edge || self.rules.king_capture == KingCapture::SurroundedOnEdge)
+            }
         }
     }
 
+    fn destinations(&self, piece: Piece, square: Square, magic_table: Option<&MagicTable>) -> M {
+        let layout = self.layout;
+        let mut moves = self.moves(square, magic_table);
+        if self.rules.throne_closed(piece) {
+            moves &= !layout.throne();
+        } else if !self.rules.throne_passable {
+            moves |= self.slide(square, self.bitboard.all(), magic_table) & layout.throne();
+        }
+
+        moves & !self.rules.restricted(piece, layout)
+    }
+
+    pub fn legal_actions(&self, magic_table: Option<&MagicTable>) -> ActionList<ACTIONS> {
+        let mut actions = ActionList::new();
+        self.legal_actions_into(&mut actions, magic_table);
+        actions
+    }
+
+    pub fn legal_actions_into(&self, actions: &mut ActionList<ACTIONS>, magic_table: Option<&MagicTable>) {
+        actions.clear();
+
+        for from in self.turn_mask().unwrap_or_default().iter_squares(self.geometry()) {
+            let piece = match self.bitboard[Piece::King].get(self.geometry().index(from)) {
+                true => Piece::King,
+                false => self.state.turn,
+            };
+
+            for to in self.destinations(piece, from, magic_table).iter_squares(self.geometry()) {
+                actions.push(Action::new(piece, from, to));
+            }
+        }
+    }
+
+    pub fn legal_action_count(&self, magic_table: Option<&MagicTable>) -> usize {
+        let mut count = 0;
+        for from in self.turn_mask().unwrap_or_default().iter_squares(self.geometry()) {
+            let piece = match self.bitboard[Piece::King].get(self.geometry().index(from)) {
+                true => Piece::King,
+                false => self.state.turn,
+            };
+
+            count += self.destinations(piece, from, magic_table).count() as usize;
+        }
+
+        count
+    }
+
+    fn has_legal_action(&self, magic_table: Option<&MagicTable>) -> bool {
+        for from in self.turn_mask().unwrap_or_default().iter_squares(self.geometry()) {
+            let piece = match self.bitboard[Piece::King].get(self.geometry().index(from)) {
+                true => Piece::King,
+                false => self.state.turn,
+            };
+
+            if !self.destinations(piece, from, magic_table).is_empty() {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn toggle_turn(&mut self) -> VikingChessResult<()> {
+        let turn = self.state.turn.opposite()?;
+
+        self.state.zobrist_hash ^= self.zobrist_table.turn_key(self.state.turn) ^ self.zobrist_table.turn_key(turn);
+        self.state.turn = turn;
+        Ok(())
+    }
+
     pub fn move_piece(
         &mut self,
         action: Action,
         magic_table: Option<&MagicTable>,
     ) -> VikingChessResult<()> {
-        if !action.valid(&self.bitboard) {
-            panic!("There is no {:?} in start_square {:?}", action.piece, action.from);
+        let geometry = self.geometry();
+        let turn_mask = self.turn_mask()?;
+        if action.piece >= Piece::Length {
+            return Err(VikingChessError::InvalidPieceKind(action.piece));
+        } else if let Some(square) = [action.from, action.to].into_iter().find(|&square| !geometry.contains(square)) {
+            return Err(VikingChessError::InvalidSquare {
+                col: square.col as i32,
+                row: square.row as i32,
+            });
+        } else if self.outcome_with_magic_table(magic_table).is_some() {
+            return Err(VikingChessError::GameOver);
+        } else if !action.valid(&self.bitboard) {
+            return Err(VikingChessError::NoPieceOnSquare {
+                piece: action.piece,
+                square: action.from,
+            });
+        } else if !action.turn_valid(turn_mask, geometry) {
+            return Err(VikingChessError::NotYourTurn { piece: action.piece });
+        } else if self.rules.restricted(action.piece, self.layout).get(geometry.index(action.to)) {
+            return Err(VikingChessError::RestrictedSquare {
+                square: action.to,
+                reason: Restriction::KingOnly,
+            });
+        } else if self.rules.throne_closed(action.piece) && self.layout.throne().get(geometry.index(action.to)) {
+            return Err(VikingChessError::RestrictedSquare {
+                square: action.to,
+                reason: Restriction::Throne,
+            });
         }
 
-        if !action.turn_valid(self.turn_mask()) {
-            return Err(format!("{:?} does not have the current turn yet.", action.piece).into());
-        } else if (action.piece != Piece::King) && ((action.to.mask() & Mask::CORNER_MASK) > Mask(0)) {
-            return Err("Pieces can't move to the corner besides the king.".to_string().into());
-        } else if action.to.mask() & Mask::THRONE_MASK > Mask(0) {
-            return Err("No one can go to the throne.".to_string().into());
+        let moves = self.destinations(action.piece, action.from, magic_table);
+        if !moves.get(geometry.index(action.to)) {
+            let aligned = action.from != action.to && (action.from.row == action.to.row || action.from.col == action.to.col);
+            return Err(match aligned {
+                true => VikingChessError::PathBlocked {
+                    from: action.from,
+                    to: action.to,
+                },
+                false => VikingChessError::IllegalMove {
+                    from: action.from,
+                    to: action.to,
+                },
+            });
         }
 
-        let moves = self.moves(action.from, magic_table);
-        if !moves & action.to.mask() > Mask(0) {
-            return Err("Invalid move.".to_string().into());
-        }
+        self.play_unchecked(action)
+    }
 
-        self.bitboard[action.piece] &= !action.from.mask();
-        self.bitboard[action.piece] |= action.to.mask();
+    pub(crate) fn play_unchecked(&mut self, action: Action) -> VikingChessResult<()> {
+        let geometry = self.geometry();
+        self.bitboard[action.piece] &= !self.layout.mask(action.from);
+        self.bitboard[action.piece] |= self.layout.mask(action.to);
+
+        self.state.zobrist_hash ^= self.zobrist_table.piece_key(geometry, action.piece, action.from);
+        self.state.zobrist_hash ^= self.zobrist_table.piece_key(geometry, action.piece, action.to);
+
+        let captured = self.captures(action);
+        for piece in Piece::ALL {
+            self.bitboard[piece] &= !captured[piece];
+            for square in captured[piece].iter_squares(geometry) {
+                self.state.zobrist_hash ^= self.zobrist_table.piece_key(geometry, piece, square);
+            }
+        }
 
-        self.state.zobrist_hash ^= self.zobrist_table[(action.piece, action.from)];
-        self.state.zobrist_hash ^= self.zobrist_table[(action.piece, action.to)];
         self.state.action = Some(action);
-        self.toggle_turn();
+        self.state.captured = captured;
+        self.toggle_turn()?;
         self.history.push(self.state);
+        debug_assert_eq!(self.state.zobrist_hash, self.calculate_hash());
+
+        Ok(())
+    }
+
+    pub fn perft(&mut self, depth: usize, magic_table: Option<&MagicTable>) -> u64 {
+        if depth == 0 {
+            return 1;
+        } else if self.outcome_with_magic_table(magic_table).is_some() {
+            return 0;
+        } else if depth == 1 {
+            return self.legal_action_count(magic_table) as u64;
+        }
+
+        let mut nodes = 0;
+        for &action in &self.legal_actions(magic_table) {
+            if self.play_unchecked(action).is_ok() {
+                nodes += self.perft(depth - 1, magic_table);
+                let _ = self.undo();
+            }
+        }
+
+        nodes
+    }
+
+    pub fn divide(&mut self, depth: usize, magic_table: Option<&MagicTable>) -> Vec<(Action, u64)> {
+        if depth == 0 || self.outcome_with_magic_table(magic_table).is_some() {
+            return Vec::new();
+        }
+
+        let mut divided = Vec::new();
+        for &action in &self.legal_actions(magic_table) {
+            if self.play_unchecked(action).is_ok() {
+                divided.push((action, self.perft(depth - 1, magic_table)));
+                let _ = self.undo();
+            }
+        }
+
+        divided
+    }
+
+    pub fn undo(&mut self) -> VikingChessResult<Action> {
+        if self.history.len() < 2 {
+            return Err(VikingChessError::NothingToUndo);
+        }
+
+        let state = self.history.pop().ok_or(VikingChessError::NothingToUndo)?;
+        let action = state.action.ok_or(VikingChessError::NothingToUndo)?;
+
+        self.bitboard[action.piece] &= !self.layout.mask(action.to);
+        self.bitboard[action.piece] |= self.layout.mask(action.from);
+
+        for piece in Piece::ALL {
+            self.bitboard[piece] |= state.captured[piece];
+        }
+
+        self.state = *self.history.last().ok_or(VikingChessError::NothingToUndo)?;
+
+        Ok(action)
+    }
+
+    pub fn undo_n(&mut self, n: usize) -> VikingChessResult<()> {
+        if self.history.len() <= n {
+            return Err(VikingChessError::UndoOutOfRange {
+                requested: n,
+                available: self.history.len() - 1,
+            });
+        }
+
+        for _ in 0..n {
+            self.undo()?;
+        }
 
         Ok(())
     }
 
+    pub fn outcome(&self) -> Option<GameResult> {
+        self.outcome_with_magic_table(None)
+    }
+
+    pub fn outcome_with_magic_table(&self, magic_table: Option<&MagicTable>) -> Option<GameResult> {
+        let king = self.bitboard[Piece::King];
+        let win = |winner, reason| Some(GameResult::Win { winner, reason });
+
+        if !(king & self.rules.escape_squares(self.layout)).is_empty() {
+            win(Piece::Defender, WinReason::KingEscaped)
+        } else if king.is_empty() {
+            win(Piece::Attacker, WinReason::KingCaptured)
+        } else if self.rules.edge_fort && self.edge_fort() {
+            win(Piece::Defender, WinReason::EdgeFort)
+        } else if self.encircled() {
+            win(Piece::Attacker, WinReason::Encircled)
+        } else if self.repetitions() + 1 >= Self::REPETITION_LIMIT {
+            match self.rules.repetition {
+                Repetition::Draw => Some(GameResult::Draw { reason: DrawReason::Repetition }),
+                Repetition::AttackersWin => win(Piece::Attacker, WinReason::Repetition),
+                Repetition::DefendersWin => win(Piece::Defender, WinReason::Repetition),
+            }
+        } else if self.move_limit.is_some_and(|limit| self.move_count() >= limit) {
+            Some(GameResult::Draw { reason: DrawReason::MoveLimit })
+        } else if !self.has_legal_action(magic_table) {
+            let winner = match self.state.turn {
+                Piece::Attacker => Piece::Defender,
+                _ => Piece::Attacker,
+            };
+
+            win(winner, WinReason::NoLegalMoves)
+        } else {
+            None
+        }
+    }
+
     pub fn is_winner(&self, piece: Piece) -> bool {
-        match piece {
-            Piece::Attacker => self.bitboard[Piece::King] == Mask(0),
-            Piece::Defender | Piece::King => self.bitboard[Piece::King] & Mask::CORNER_MASK > Mask(0),
-            _ => false,
+        let piece = match piece {
+            Piece::King => Piece::Defender,
+            piece => piece,
+        };
+
+        self.outcome().and_then(|result| result.winner()) == Some(piece)
+    }
+
+    fn encircled(&self) -> bool {
+        let defenders = self.bitboard[Piece::Defender] | self.bitboard[Piece::King];
+        let open = !self.bitboard[Piece::Attacker];
+        let layout = self.layout;
+        (layout.flood_fill(layout.edges() & open, open) & defenders).is_empty()
+    }
+
+    fn edge_fort(&self) -> bool {
+        let layout = self.layout;
+        let geometry = layout.geometry();
+        let king = self.bitboard[Piece::King];
+        let Some(square) = king.iter_squares(geometry).next() else {
+            return false;
+        };
+
+        if (king & layout.edges()).is_empty() || self.destinations(Piece::King, square, None).is_empty() {
+            return false;
         }
+
+        let defenders = self.bitboard[Piece::Defender];
+        let interior = layout.flood_fill(king, !defenders);
+        if !(interior & self.bitboard[Piece::Attacker]).is_empty() {
+            return false;
+        }
+
+        let mut hostile = self.rules.hostile_corners(layout);
+        if self.rules.throne_hostile_to_defenders {
+            hostile |= layout.throne() & !self.bitboard.all();
+        }
+
+        let wall = layout.spread(interior) & defenders;
+        let mut solid = defenders;
+        loop {
+            let safe = (interior & !hostile) | solid;
+            let mut holding = solid;
+
+            for square in solid.iter_squares(geometry) {
+                if !self.unbreakable(square, safe) {
+                    holding &= !layout.mask(square);
+                }
+            }
+
+            if holding == solid {
+                return (wall & !solid).is_empty();
+            }
+
+            solid = holding;
+        }
+    }
+
+    fn unbreakable(&self, square: Square, safe: M) -> bool {
+        let geometry = self.geometry();
+        let last = geometry.length() as i8 - 1;
+        let (row, col) = (square.row as i8, square.col as i8);
+        let shielded = |row: i8, col: i8| match (0..=last).contains(&row) && (0..=last).contains(&col) {
+            true => safe.get(geometry.index(Square::new(row as u8, col as u8))),
+            false => true,
+        };
+
+        let vertical = shielded(row - 1, col) || shielded(row + 1, col);
+        let horizontal = shielded(row, col - 1) || shielded(row, col + 1);
+        let inward = match (row, col) {
+            (0, _) => shielded(1, col),
+            (_, 0) => shielded(row, 1),
+            _ if row == last => shielded(last - 1, col),
+            _ if col == last => shielded(row, last - 1),
+            _ => true,
+        };
+
+        vertical && horizontal && (inward || !self.rules.shieldwall)
     }
 }
 
-impl Display for Board {
+impl<M: BitSet, const ACTIONS: usize> Display for GenericBoard<M, ACTIONS> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.bitboard)
+        write!(f, "{}", self.to_fen())
+    }
+}
+
+impl<M: BitSet, const ACTIONS: usize> FromStr for GenericBoard<M, ACTIONS> {
+    type Err = VikingChessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_fen(s)
     }
 }
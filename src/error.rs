@@ -0,0 +1,97 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::io;
+
+use crate::fen::FenError;
+use crate::piece::Piece;
+use crate::square::Square;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Restriction {
+    KingOnly,
+    Throne,
+}
+
+#[derive(Debug)]
+pub enum VikingChessError {
+    NotYourTurn { piece: Piece },
+    NoPieceOnSquare { piece: Piece, square: Square },
+    PathBlocked { from: Square, to: Square },
+    IllegalMove { from: Square, to: Square },
+    RestrictedSquare { square: Square, reason: Restriction },
+    InvalidTurn { turn: Piece },
+    GameOver,
+    NothingToUndo,
+    UndoOutOfRange { requested: usize, available: usize },
+    InvalidFen(FenError),
+    InvalidPiece(char),
+    InvalidPieceKind(Piece),
+    InvalidSquare { col: i32, row: i32 },
+    InvalidSquareIndex { index: usize },
+    UnsupportedBoardSize { length: usize },
+    BoardTooLarge { length: usize, bits: usize },
+    MagicNotFound { square: Square },
+    MagicTableLoad(String),
+    Io(io::Error),
+}
+
+impl Display for Restriction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Restriction::KingOnly => write!(f, "only the king can go there"),
+            Restriction::Throne => write!(f, "the throne can't be re-entered"),
+        }
+    }
+}
+
+impl Display for VikingChessError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            VikingChessError::NotYourTurn { piece } => write!(f, "{piece:?} does not have the current turn yet."),
+            VikingChessError::NoPieceOnSquare { piece, square } => write!(f, "There is no {piece:?} on {square:?}."),
+            VikingChessError::PathBlocked { from, to } => write!(f, "The path from {from:?} to {to:?} is blocked."),
+            VikingChessError::IllegalMove { from, to } => write!(f, "Pieces can't move from {from:?} to {to:?}."),
+            VikingChessError::RestrictedSquare { square, reason } => write!(f, "{square:?} is restricted: {reason}."),
+            VikingChessError::InvalidTurn { turn } => write!(f, "{turn:?} can't have the current turn."),
+            VikingChessError::GameOver => write!(f, "The game is already over."),
+            VikingChessError::NothingToUndo => write!(f, "There is no move to undo."),
+            VikingChessError::UndoOutOfRange { requested, available } => {
+                write!(f, "Cannot undo {requested} moves; only {available} were played.")
+            }
+            VikingChessError::InvalidFen(error) => write!(f, "{error}"),
+            VikingChessError::InvalidPiece(ch) => write!(f, "Failure to convert {ch:?} to Piece."),
+            VikingChessError::InvalidPieceKind(piece) => write!(f, "{piece:?} is not a piece that can be played."),
+            VikingChessError::InvalidSquare { col, row } => write!(f, "Invalid square position ({col}, {row})."),
+            VikingChessError::InvalidSquareIndex { index } => write!(f, "Invalid square index {index}."),
+            VikingChessError::UnsupportedBoardSize { length } => write!(f, "Boards of length {length} are not supported."),
+            VikingChessError::BoardTooLarge { length, bits } => write!(f, "Boards of length {length} do not fit in {bits} bits."),
+            VikingChessError::MagicNotFound { square } => write!(f, "No magic found for {square:?}."),
+            VikingChessError::MagicTableLoad(reason) => write!(f, "Failure to load the magic table: {reason}"),
+            VikingChessError::Io(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for VikingChessError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            VikingChessError::InvalidFen(error) => Some(error),
+            VikingChessError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<FenError> for VikingChessError {
+    fn from(value: FenError) -> Self {
+        VikingChessError::InvalidFen(value)
+    }
+}
+
+impl From<io::Error> for VikingChessError {
+    fn from(value: io::Error) -> Self {
+        VikingChessError::Io(value)
+    }
+}
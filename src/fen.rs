@@ -0,0 +1,195 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use crate::piece::Piece;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FenErrorKind {
+    Empty,
+    UnknownCharacter(char),
+    ZeroSkip,
+    RowTooLong,
+    RowTooShort,
+    TooManyRows,
+    TooFewRows,
+    UnsupportedSize(usize),
+    MultipleKings,
+    RestrictedSquare(Piece),
+    MissingTurn,
+    InvalidTurn,
+    TrailingInput,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct FenError {
+    pub kind: FenErrorKind,
+    pub row: usize,
+    pub col: usize,
+}
+
+impl FenError {
+    pub fn new(kind: FenErrorKind, row: usize, col: usize) -> Self {
+        Self { kind, row, col }
+    }
+}
+
+impl Display for FenErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FenErrorKind::Empty => write!(f, "the notation is empty"),
+            FenErrorKind::UnknownCharacter(ch) => write!(f, "unknown character {ch:?}"),
+            FenErrorKind::ZeroSkip => write!(f, "empty squares must be skipped by at least one"),
+            FenErrorKind::RowTooLong => write!(f, "the row has too many squares"),
+            FenErrorKind::RowTooShort => write!(f, "the row has too few squares"),
+            FenErrorKind::TooManyRows => write!(f, "the board has too many rows"),
+            FenErrorKind::TooFewRows => write!(f, "the board has too few rows"),
+            FenErrorKind::UnsupportedSize(length) => write!(f, "boards of length {length} are not supported"),
+            FenErrorKind::MultipleKings => write!(f, "the board has more than one king"),
+            FenErrorKind::RestrictedSquare(piece) => write!(f, "{piece:?} is on a restricted square"),
+            FenErrorKind::MissingTurn => write!(f, "the current turn is not specified"),
+            FenErrorKind::InvalidTurn => write!(f, "the current turn must be B or W"),
+            FenErrorKind::TrailingInput => write!(f, "unexpected input after the current turn"),
+        }
+    }
+}
+
+impl Display for FenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid FEN at row {}, column {}: {}.", self.row, self.col, self.kind)
+    }
+}
+
+impl Error for FenError {}
+
+pub(crate) struct Placement {
+    pub length: usize,
+    pub pieces: Vec<(Piece, usize, usize)>,
+}
+
+pub(crate) fn parse_placement(str: &str, supported: impl Fn(usize) -> bool) -> Result<Placement, FenError> {
+    let rows: Vec<&str> = str.split('/').collect();
+    if str.is_empty() {
+        return Err(FenError::new(FenErrorKind::Empty, 0, 0));
+    }
+
+    let width = rank_width(rows[0]);
+    let length = [width, rows.len()]
+        .into_iter()
+        .find(|&length| supported(length))
+        .ok_or(FenError::new(FenErrorKind::UnsupportedSize(width), 0, 0))?;
+
+    if rows.len() > length {
+        return Err(FenError::new(FenErrorKind::TooManyRows, length, 0));
+    }
+
+    let mut pieces = Vec::new();
+    for (row, rank) in rows.iter().enumerate() {
+        let mut col = 0;
+        let mut chars = rank.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            if col >= length {
+                return Err(FenError::new(FenErrorKind::RowTooLong, row, col));
+            }
+
+            if let Some(digit) = ch.to_digit(10) {
+                let mut skip = digit as usize;
+                while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                    skip = skip.saturating_mul(10).saturating_add(digit as usize);
+                    chars.next();
+                }
+
+                if skip == 0 {
+                    return Err(FenError::new(FenErrorKind::ZeroSkip, row, col));
+                } else if col.saturating_add(skip) > length {
+                    return Err(FenError::new(FenErrorKind::RowTooLong, row, col));
+                }
+
+                col += skip;
+                continue;
+            }
+
+            let piece = Piece::try_from(ch).map_err(|_| FenError::new(FenErrorKind::UnknownCharacter(ch), row, col))?;
+            if piece == Piece::King && pieces.iter().any(|&(piece, _, _)| piece == Piece::King) {
+                return Err(FenError::new(FenErrorKind::MultipleKings, row, col));
+            }
+
+            pieces.push((piece, row, col));
+            col += 1;
+        }
+
+        if col < length {
+            return Err(FenError::new(FenErrorKind::RowTooShort, row, col));
+        }
+    }
+
+    if rows.len() < length {
+        return Err(FenError::new(FenErrorKind::TooFewRows, rows.len(), 0));
+    }
+
+    Ok(Placement { length, pieces })
+}
+
+pub(crate) fn parse_turn<'a>(mut fields: impl Iterator<Item = &'a str>, length: usize) -> Result<Piece, FenError> {
+    let turn = match fields.next() {
+        Some("B") => Piece::Attacker,
+        Some("W") => Piece::Defender,
+        Some(_) => return Err(FenError::new(FenErrorKind::InvalidTurn, length, 0)),
+        None => return Err(FenError::new(FenErrorKind::MissingTurn, length, 0)),
+    };
+
+    match fields.next() {
+        Some(_) => Err(FenError::new(FenErrorKind::TrailingInput, length, 0)),
+        None => Ok(turn),
+    }
+}
+
+pub(crate) fn write_placement(length: usize, piece_at: impl Fn(usize, usize) -> Option<Piece>) -> String {
+    let mut fen = String::new();
+
+    for row in 0..length {
+        let mut empty = 0;
+        for col in 0..length {
+            match piece_at(row, col) {
+                Some(piece) => {
+                    if empty > 0 {
+                        fen += &empty.to_string();
+                        empty = 0;
+                    }
+
+                    fen.push(piece.symbol());
+                }
+                None => empty += 1,
+            }
+        }
+
+        if empty > 0 {
+            fen += &empty.to_string();
+        }
+
+        if row + 1 < length {
+            fen.push('/');
+        }
+    }
+
+    fen
+}
+
+fn rank_width(rank: &str) -> usize {
+    let mut width: usize = 0;
+    let mut skip: usize = 0;
+
+    for ch in rank.chars() {
+        match ch.to_digit(10) {
+            Some(digit) => skip = skip.saturating_mul(10).saturating_add(digit as usize),
+            None => {
+                width = width.saturating_add(skip).saturating_add(1);
+                skip = 0;
+            }
+        }
+    }
+
+    width.saturating_add(skip)
+}
@@ -0,0 +1,407 @@
+use std::any::Any;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+use crate::VikingChessError;
+use crate::VikingChessResult;
+use crate::bit_set::BitSet;
+use crate::direction::Direction;
+use crate::mask::Mask;
+use crate::square::Square;
+use crate::symmetry::Symmetry;
+
+type SharedLayouts = HashMap<(TypeId, Geometry), &'static (dyn Any + Send + Sync)>;
+
+static LAYOUTS: LazyLock<RwLock<SharedLayouts>> = LazyLock::new(Default::default);
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Geometry {
+    length: u8,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Layout<M: BitSet = Mask> {
+    geometry: Geometry,
+    board: M,
+    first_rank: M,
+    first_file: M,
+    last_file: M,
+    corners: M,
+    throne: M,
+    edges: M,
+    rays: [Vec<M>; 4],
+}
+
+impl Geometry {
+    pub const BRANDUBH: Geometry = Geometry { length: 7 };
+    pub const TABLUT: Geometry = Geometry { length: 9 };
+    pub const HNEFATAFL: Geometry = Geometry { length: 11 };
+    pub const TABLUT_13: Geometry = Geometry { length: 13 };
+    pub const ALEA_EVANGELII: Geometry = Geometry { length: 19 };
+    pub const ALL: [Geometry; 5] = [
+        Geometry::BRANDUBH,
+        Geometry::TABLUT,
+        Geometry::HNEFATAFL,
+        Geometry::TABLUT_13,
+        Geometry::ALEA_EVANGELII,
+    ];
+    pub const MAX_LENGTH: usize = 19;
+    pub const MAX_SQUARES: usize = Geometry::MAX_LENGTH * Geometry::MAX_LENGTH;
+
+    pub fn new(length: usize) -> VikingChessResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|geometry| geometry.length() == length)
+            .ok_or(VikingChessError::UnsupportedBoardSize { length })
+    }
+
+    pub const fn length(self) -> usize {
+        self.length as usize
+    }
+
+    pub const fn total_squares(self) -> usize {
+        self.length() * self.length()
+    }
+
+    pub(crate) const fn id(self) -> usize {
+        match self.length {
+            7 => 0,
+            9 => 1,
+            11 => 2,
+            13 => 3,
+            _ => 4,
+        }
+    }
+
+    pub fn fits<M: BitSet>(self) -> bool {
+        self.total_squares() <= M::BITS
+    }
+
+    pub fn layout<M: BitSet>(self) -> VikingChessResult<&'static Layout<M>> {
+        if !self.fits::<M>() {
+            return Err(VikingChessError::BoardTooLarge {
+                length: self.length(),
+                bits: M::BITS,
+            });
+        }
+
+        let key = (TypeId::of::<M>(), self);
+        let cached = LAYOUTS.read().unwrap_or_else(PoisonError::into_inner).get(&key).copied();
+        let layout = match cached {
+            Some(layout) => layout,
+            None => *LAYOUTS
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(key)
+                .or_insert_with(|| Box::leak(Box::new(Layout::<M>::new(self)))),
+        };
+
+        Ok(layout.downcast_ref().expect("Layouts are keyed by their bit set type."))
+    }
+
+    pub fn contains(self, square: Square) -> bool {
+        (square.row as usize) < self.length() && (square.col as usize) < self.length()
+    }
+
+    pub fn index(self, square: Square) -> usize {
+        square.row as usize * self.length() + square.col as usize
+    }
+
+    pub fn square(self, index: usize) -> VikingChessResult<Square> {
+        if index >= self.total_squares() {
+            return Err(VikingChessError::InvalidSquareIndex { index });
+        }
+
+        Ok(Square::new((index / self.length()) as u8, (index % self.length()) as u8))
+    }
+
+    pub fn squares(self) -> impl Iterator<Item = Square> {
+        (0..self.total_squares()).filter_map(move |index| self.square(index).ok())
+    }
+
+    pub fn center(self) -> Square {
+        Square::new(self.length / 2, self.length / 2)
+    }
+
+    pub fn transform_square(self, square: Square, symmetry: Symmetry) -> Square {
+        let last = self.length - 1;
+        let (row, col) = (square.row, square.col);
+
+        match symmetry {
+            Symmetry::Identity => square,
+            Symmetry::Rotate90 => Square::new(col, last - row),
+            Symmetry::Rotate180 => Square::new(last - row, last - col),
+            Symmetry::Rotate270 => Square::new(last - col, row),
+            Symmetry::FlipVertical => Square::new(last - row, col),
+            Symmetry::FlipHorizontal => Square::new(row, last - col),
+            Symmetry::Transpose => Square::new(col, row),
+            Symmetry::AntiTranspose => Square::new(last - col, last - row),
+        }
+    }
+}
+
+impl<M: BitSet> Layout<M> {
+    pub(crate) fn new(geometry: Geometry) -> Self {
+        let length = geometry.length();
+        let last = length - 1;
+        let bit = |row: usize, col: usize| M::bit(row * length + col);
+        let first_rank = M::low_bits(length);
+        let first_file = (0..length).fold(M::EMPTY, |file, row| file | bit(row, 0));
+        let last_file = first_file.shift_left(last);
+
+        let mut layout = Self {
+            geometry,
+            board: M::low_bits(length * length),
+            first_rank,
+            first_file,
+            last_file,
+            corners: bit(0, 0) | bit(0, last) | bit(last, 0) | bit(last, last),
+            throne: bit(length / 2, length / 2),
+            edges: first_rank | first_rank.shift_left(last * length) | first_file | last_file,
+            rays: Default::default(),
+        };
+
+        for direction in Direction::ALL {
+            let rays = (0..geometry.total_squares())
+                .map(|index| {
+                    let mut ray = M::EMPTY;
+                    let mut current = layout.shift(M::bit(index), direction);
+                    while !current.is_empty() {
+                        ray |= current;
+                        current = layout.shift(current, direction);
+                    }
+
+                    ray
+                })
+                .collect();
+
+            layout.rays[direction as usize] = rays;
+        }
+
+        layout
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    pub fn mask(&self, square: Square) -> M {
+        M::bit(self.geometry.index(square))
+    }
+
+    pub fn board(&self) -> M {
+        self.board
+    }
+
+    pub fn corners(&self) -> M {
+        self.corners
+    }
+
+    pub fn throne(&self) -> M {
+        self.throne
+    }
+
+    pub fn edges(&self) -> M {
+        self.edges
+    }
+
+    pub fn rank(&self, row: u8) -> M {
+        self.first_rank.shift_left(row as usize * self.geometry.length())
+    }
+
+    pub fn file(&self, col: u8) -> M {
+        self.first_file.shift_left(col as usize)
+    }
+
+    pub fn ray(&self, square: Square, direction: Direction) -> M {
+        match self.geometry.contains(square) {
+            true => self.rays[direction as usize][self.geometry.index(square)],
+            false => M::EMPTY,
+        }
+    }
+
+    pub fn between(&self, a: Square, b: Square) -> M {
+        if !self.geometry.contains(a) || !self.geometry.contains(b) {
+            return M::EMPTY;
+        }
+
+        let target = self.mask(b);
+        Direction::ALL
+            .into_iter()
+            .find(|&direction| !(self.ray(a, direction) & target).is_empty())
+            .map_or(M::EMPTY, |direction| self.ray(a, direction) & self.ray(b, direction.opposite()))
+    }
+
+    pub fn line(&self, a: Square, b: Square) -> M {
+        if !self.geometry.contains(a) || !self.geometry.contains(b) {
+            return M::EMPTY;
+        }
+
+        match (a.row == b.row, a.col == b.col) {
+            (true, false) => self.rank(a.row),
+            (false, true) => self.file(a.col),
+            _ => M::EMPTY,
+        }
+    }
+
+    pub fn shift(&self, mask: M, direction: Direction) -> M {
+        let length = self.geometry.length();
+        let mask = mask & self.board;
+
+        let shifted = match direction {
+            Direction::North => mask.shift_right(length),
+            Direction::East => mask.shift_left(1) & !self.first_file,
+            Direction::South => mask.shift_left(length),
+            Direction::West => mask.shift_right(1) & !self.last_file,
+        };
+
+        shifted & self.board
+    }
+
+    pub fn flip_vertical(&self, mask: M) -> M {
+        let (length, last) = (self.geometry.length(), self.geometry.length() - 1);
+
+        (0..length).fold(M::EMPTY, |flipped, row| {
+            flipped | (mask.shift_right(row * length) & self.first_rank).shift_left((last - row) * length)
+        })
+    }
+
+    pub fn flip_horizontal(&self, mask: M) -> M {
+        let last = self.geometry.length() - 1;
+
+        (0..self.geometry.length()).fold(M::EMPTY, |flipped, col| {
+            flipped | (mask.shift_right(col) & self.first_file).shift_left(last - col)
+        })
+    }
+
+    pub fn transpose(&self, mask: M) -> M {
+        let mut transposed = M::EMPTY;
+        for square in mask.iter_squares(self.geometry) {
+            transposed |= self.mask(Square::new(square.col, square.row));
+        }
+
+        transposed
+    }
+
+    pub fn rotate90(&self, mask: M) -> M {
+        self.flip_horizontal(self.transpose(mask))
+    }
+
+    pub fn transform(&self, mask: M, symmetry: Symmetry) -> M {
+        match symmetry {
+            Symmetry::Identity => mask & self.board,
+            Symmetry::Rotate90 => self.rotate90(mask),
+            Symmetry::Rotate180 => self.flip_vertical(self.flip_horizontal(mask)),
+            Symmetry::Rotate270 => self.flip_vertical(self.transpose(mask)),
+            Symmetry::FlipVertical => self.flip_vertical(mask),
+            Symmetry::FlipHorizontal => self.flip_horizontal(mask),
+            Symmetry::Transpose => self.transpose(mask),
+            Symmetry::AntiTranspose => self.flip_vertical(self.flip_horizontal(self.transpose(mask))),
+        }
+    }
+
+    pub fn adjacent(&self, square: Square) -> M {
+        let length = self.geometry.length() as i8;
+
+        [1, 3, 5, 7]
+            .into_iter()
+            .map(|k| (k / 3 - 1 + square.row as i8, k % 3 - 1 + square.col as i8))
+            .filter(|(r, c)| (0..length).contains(r) && (0..length).contains(c))
+            .fold(M::EMPTY, |a, (r, c)| a | M::bit(r as usize * length as usize + c as usize))
+    }
+
+    pub fn interjacent(&self, square: Square) -> M {
+        let length = self.geometry.length() as i8;
+
+        [2, 10, 14, 22]
+            .into_iter()
+            .map(|k| (k / 5 - 2 + square.row as i8, k % 5 - 2 + square.col as i8))
+            .filter(|(r, c)| (0..length).contains(r) && (0..length).contains(c))
+            .fold(M::EMPTY, |a, (r, c)| a | M::bit(r as usize * length as usize + c as usize))
+    }
+
+    pub fn moves(&self, square: Square) -> M {
+        (self.file(square.col) | self.rank(square.row)) & !self.mask(square)
+    }
+
+    pub fn blockers(&self, square: Square) -> M {
+        let last = self.geometry.length() as u8 - 1;
+        let cols = self.file(0) | self.file(last);
+        let rows = self.rank(0) | self.rank(last);
+        let corners = self.corners();
+        let mut potential_blockers = self.moves(square) & !(cols | rows);
+
+        let column = self.file(square.col);
+        let row = self.rank(square.row);
+        let file_edge = square.col == 0 || square.col == last;
+        let rank_edge = square.row == 0 || square.row == last;
+
+        match (file_edge, rank_edge) {
+            (true, true) => {
+                potential_blockers |= column & !corners;
+                potential_blockers |= row & !corners;
+            }
+            (true, false) => {
+                potential_blockers |= column & !corners & !self.mask(square);
+            }
+            (false, true) => {
+                potential_blockers |= row & !corners & !self.mask(square);
+            }
+            (false, false) => {}
+        }
+
+        potential_blockers
+    }
+
+    pub fn legal_moves(&self, square: Square, blockers: M) -> M {
+        let length = self.geometry.length() as i32;
+        let (row, col) = (square.row as i32, square.col as i32);
+        let rays = [(length, length - 1 - row), (-length, row), (1, length - 1 - col), (-1, col)];
+        let mut legal_moves = M::EMPTY;
+
+        for (step, distance) in rays {
+            let mut index = self.geometry.index(square) as i32;
+            for _ in 0..distance {
+                index += step;
+                if blockers.get(index as usize) {
+                    break;
+                }
+
+                legal_moves |= M::bit(index as usize);
+            }
+        }
+
+        legal_moves
+    }
+
+    pub fn spread(&self, mask: M) -> M {
+        let length = self.geometry.length();
+        let east = mask.shift_left(1) & !self.first_file;
+        let west = mask.shift_right(1) & !self.last_file;
+        let north = mask.shift_right(length);
+        let south = mask.shift_left(length);
+
+        (east | west | north | south) & self.board
+    }
+
+    pub fn flood_fill(&self, seed: M, open: M) -> M {
+        let mut filled = seed & open;
+        loop {
+            let next = (filled | self.spread(filled)) & open;
+            if next == filled {
+                return filled;
+            }
+
+            filled = next;
+        }
+    }
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Self::TABLUT
+    }
+}
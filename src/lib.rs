@@ -1,27 +1,70 @@
-use std::error::Error;
+pub use crate::error::VikingChessError;
 
-pub type VikingChessError = Box<dyn Error>;
 pub type VikingChessResult<T> = Result<T, VikingChessError>;
 
 mod action;
+mod bit_set;
 mod bitboard;
 mod board;
+mod direction;
+mod error;
+mod fen;
+mod geometry;
 mod magics;
 mod mask;
+mod outcome;
 mod piece;
+mod rules;
+mod search;
 mod square;
 mod state;
+mod symmetry;
+mod transposition;
+mod wide_mask;
 mod zobrist;
 
 #[cfg(test)]
 mod tests;
 
 pub mod prelude {
+    pub use crate::bit_set::BitSet;
     pub use crate::bitboard::Bitboard;
     pub use crate::board::Board;
+    pub use crate::board::Board13;
+    pub use crate::board::Board19;
+    pub use crate::board::WideBoard;
+    pub use crate::direction::Direction;
+    pub use crate::error::Restriction;
+    pub use crate::fen::FenError;
+    pub use crate::fen::FenErrorKind;
+    pub use crate::geometry::Geometry;
+    pub use crate::geometry::Layout;
+    pub use crate::magics::HashMagicTable;
     pub use crate::magics::MagicTable;
     pub use crate::mask::Mask;
+    pub use crate::mask::MaskDisplay;
+    pub use crate::outcome::DrawReason;
+    pub use crate::outcome::GameResult;
+    pub use crate::outcome::WinReason;
     pub use crate::piece::Piece;
+    pub use crate::rules::Escape;
+    pub use crate::rules::KingCapture;
+    pub use crate::rules::Repetition;
+    pub use crate::rules::RuleSet;
+    pub use crate::search::Search;
+    pub use crate::search::SearchLimits;
+    pub use crate::search::SearchResult;
     pub use crate::square::Square;
+    pub use crate::symmetry::Symmetry;
+    pub use crate::transposition::Bound;
+    pub use crate::transposition::TranspositionEntry;
+    pub use crate::transposition::TranspositionStats;
+    pub use crate::transposition::TranspositionTable;
+    pub use crate::wide_mask::Mask13;
+    pub use crate::wide_mask::Mask19;
+    pub use crate::wide_mask::WideMask;
+    pub use crate::zobrist::ZobristTable;
     pub use crate::action::Action;
+    pub use crate::action::ActionList;
+    pub use crate::action::WideActionList;
 }
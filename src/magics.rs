@@ -1,56 +1,408 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
 
+use rand::RngCore;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
 use ron::de::SpannedError;
+use ron::ser::PrettyConfig;
 use serde::Deserialize;
 use serde::Serialize;
+use serde::Serializer;
 
+use crate::VikingChessError;
+use crate::VikingChessResult;
 use crate::mask::Mask;
-use crate::prelude::Bitboard;
+use crate::geometry::Geometry;
+use crate::geometry::Layout;
+use crate::square::Square;
 
-#[derive(Debug, Serialize, Deserialize)]
+include!(concat!(env!("OUT_DIR"), "/magics.rs"));
+
+static EMBEDDED_CACHE: [OnceLock<MagicTable>; EMBEDDED_TABLES.len()] = [const { OnceLock::new() }; EMBEDDED_TABLES.len()];
+
+#[repr(C, align(16))]
+pub(crate) struct Aligned<T: ?Sized>(T);
+
+pub(crate) struct EmbeddedTable {
+    length: usize,
+    magics: &'static [Mask],
+    blockers: &'static [Mask],
+    shifts: &'static [u32],
+    offsets: &'static [usize],
+    moves: &'static Aligned<[u8]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "MagicNumbers", into = "MagicNumbers")]
 pub struct MagicTable {
+    pub geometry: Geometry,
+    pub shifts: Cow<'static, [u32]>,
+    pub magics: Cow<'static, [Mask]>,
+    pub blockers: Cow<'static, [Mask]>,
+    pub offsets: Cow<'static, [usize]>,
+    pub moves: Cow<'static, [Mask]>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MagicNumbers {
+    magics: Vec<Mask>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "HashMagicMoves", into = "HashMagicMoves")]
+pub struct HashMagicTable {
+    pub geometry: Geometry,
     pub magics: Vec<Mask>,
+    pub blockers: Vec<Mask>,
     pub moves: Vec<HashMap<Mask, Mask>>,
 }
 
+#[derive(Serialize, Deserialize)]
+struct HashMagicMoves {
+    magics: Vec<Mask>,
+    #[serde(serialize_with = "serialize_sorted")]
+    moves: Vec<HashMap<Mask, Mask>>,
+}
+
 impl MagicTable {
     pub const MAGICS_PATH: &'static str = "./assets/magics.ron";
+    pub const BRANDUBH_MAGICS_PATH: &'static str = "./assets/brandubh_magics.ron";
+    pub const DEFAULT_SEED: u64 = 0x7AB1_u64;
+    pub const MAX_ATTEMPTS: usize = 1 << 24;
+
+    pub fn shifts(layout: &Layout) -> Vec<u32> {
+        layout.geometry().squares().map(|square| layout.blockers(square).count_ones()).collect()
+    }
+
+    pub fn index(blockers: Mask, magic: Mask, shift: u32) -> usize {
+        (blockers.wrapping_mul(magic.0) >> (128 - shift)) as usize
+    }
+
+    pub fn from_magics(magics: Vec<Mask>) -> VikingChessResult<Self> {
+        let geometry = geometry_of(&magics)?;
+        let layout = geometry.layout::<Mask>()?;
+        let shifts = Self::shifts(layout);
+        let mut blockers = Vec::with_capacity(magics.len());
+        let mut offsets = Vec::with_capacity(magics.len());
+        let mut moves = Vec::new();
+
+        for (index, &magic) in magics.iter().enumerate() {
+            let square = geometry.square(index)?;
+            let shift = shifts[index];
+            let relevant = layout.blockers(square);
+            let offset = moves.len();
+            let mut filled = vec![false; 1 << shift];
+
+            moves.resize(offset + (1 << shift), Mask(0));
+            for subset in subsets(relevant) {
+                let key = Self::index(subset, magic, shift);
+                let legal_moves = layout.legal_moves(square, subset);
+
+                if filled[key] && moves[offset + key] != legal_moves {
+                    return Err(VikingChessError::MagicTableLoad(format!("magic of {square:?} has a destructive collision.")));
+                }
+
+                filled[key] = true;
+                moves[offset + key] = legal_moves;
+            }
+
+            blockers.push(relevant);
+            offsets.push(offset);
+        }
+
+        Ok(Self {
+            geometry,
+            shifts: shifts.into(),
+            magics: magics.into(),
+            blockers: blockers.into(),
+            offsets: offsets.into(),
+            moves: moves.into(),
+        })
+    }
+
+    pub fn embedded(geometry: Geometry) -> Option<&'static MagicTable> {
+        let slot = EMBEDDED_TABLES.iter().position(|table| table.length == geometry.length())?;
+        let table = &EMBEDDED_TABLES[slot];
+
+        Some(EMBEDDED_CACHE[slot].get_or_init(|| {
+            // SAFETY: `Aligned` places the bytes on a 16 byte boundary, `Mask` is a transparent `u128` that is valid
+            // for any bit pattern, and build.rs writes whole masks in the target's byte order.
+            let (prefix, moves, suffix) = unsafe { table.moves.0.align_to::<Mask>() };
+            assert!(prefix.is_empty() && suffix.is_empty(), "The embedded magic moves are misaligned.");
+
+            MagicTable {
+                geometry,
+                shifts: Cow::Borrowed(table.shifts),
+                magics: Cow::Borrowed(table.magics),
+                blockers: Cow::Borrowed(table.blockers),
+                offsets: Cow::Borrowed(table.offsets),
+                moves: Cow::Borrowed(moves),
+            }
+        }))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> VikingChessResult<Self> {
+        MagicTable::try_from(fs::read_to_string(path)?)
+    }
+
+    pub fn moves(&self, square: Square, blockers: Mask) -> Mask {
+        let index = self.geometry.index(square);
+        let key = Self::index(blockers & self.blockers[index], self.magics[index], self.shifts[index]);
+        self.moves[self.offsets[index] + key]
+    }
+
+    pub fn generate(seed: u64) -> VikingChessResult<Self> {
+        Self::generate_with_geometry(Geometry::TABLUT, seed)
+    }
+
+    pub fn generate_with_geometry(geometry: Geometry, seed: u64) -> VikingChessResult<Self> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut magics = Vec::with_capacity(geometry.total_squares());
+
+        for square in geometry.squares() {
+            magics.push(Self::find_magic(geometry, square, &mut rng)?);
+        }
+
+        let table = MagicTable::from_magics(magics)?;
+        table.verify()?;
+
+        Ok(table)
+    }
+
+    pub(crate) fn find_magic(geometry: Geometry, square: Square, rng: &mut StdRng) -> VikingChessResult<Mask> {
+        let layout = geometry.layout::<Mask>()?;
+        let relevant = layout.blockers(square);
+        let shift = relevant.count_ones();
+
+        let mut occupancies: Vec<(Mask, Mask)> = subsets(relevant)
+            .map(|blockers| (blockers, layout.legal_moves(square, blockers)))
+            .collect();
+        occupancies.shuffle(rng);
+
+        let mut used: Vec<(usize, Mask)> = vec![(0, Mask(0)); 1 << shift];
+
+        for attempt in 1..=Self::MAX_ATTEMPTS {
+            let magic = Mask(random_u128(rng) & random_u128(rng) & random_u128(rng));
+            if (relevant.wrapping_mul(magic.0) >> 112).count_ones() < 8 {
+                continue;
+            }
+
+            let collision_free = occupancies.iter().all(|&(blockers, moves)| {
+                let entry = &mut used[Self::index(blockers, magic, shift)];
+                if entry.0 != attempt {
+                    *entry = (attempt, moves);
+                }
+
+                entry.1 == moves
+            });
+
+            if collision_free {
+                return Ok(magic);
+            }
+        }
+
+        Err(VikingChessError::MagicNotFound { square })
+    }
+
+    pub fn verify(&self) -> VikingChessResult<()> {
+        let layout = self.geometry.layout::<Mask>()?;
+        for square in self.geometry.squares() {
+            for blockers in subsets(layout.blockers(square)) {
+                if self.moves(square, blockers) != layout.legal_moves(square, blockers) {
+                    return Err(VikingChessError::MagicTableLoad(format!("magic of {square:?} fails for blockers {}.", blockers.0)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> VikingChessResult<()> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let ron = ron::ser::to_string_pretty(self, PrettyConfig::default()).map_err(io::Error::other)?;
+        fs::write(path, ron)?;
+        Ok(())
+    }
+}
+
+impl HashMagicTable {
+    pub fn moves(&self, square: Square, blockers: Mask) -> Mask {
+        let index = self.geometry.index(square);
+        let relevant = self.blockers[index];
+        let key = MagicTable::index(blockers & relevant, self.magics[index], relevant.count_ones());
+        self.moves[index][&Mask(key as u128)]
+    }
+}
+
+fn geometry_of(magics: &[Mask]) -> VikingChessResult<Geometry> {
+    let geometries = Geometry::ALL.into_iter().filter(|geometry| geometry.fits::<Mask>());
+    geometries.clone().find(|geometry| geometry.total_squares() == magics.len()).ok_or_else(|| {
+        let expected = geometries.map(|geometry| geometry.total_squares().to_string()).collect::<Vec<_>>().join(", ");
+        VikingChessError::MagicTableLoad(format!("expected one of {expected} magics, found {}.", magics.len()))
+    })
+}
+
+fn random_u128(rng: &mut StdRng) -> u128 {
+    (rng.next_u64() as u128) << 64 | rng.next_u64() as u128
+}
+
+fn subsets(mask: Mask) -> impl Iterator<Item = Mask> {
+    let mut subset = Some(Mask(0));
+
+    std::iter::from_fn(move || {
+        let current = subset?;
+        let next = Mask(current.0.wrapping_sub(mask.0) & mask.0);
+        subset = (next != Mask(0)).then_some(next);
+        Some(current)
+    })
+}
+
+fn serialize_sorted<S>(moves: &[HashMap<Mask, Mask>], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let sorted: Vec<BTreeMap<&Mask, &Mask>> = moves.iter().map(|m| m.iter().collect()).collect();
+    sorted.serialize(serializer)
+}
+
+impl TryFrom<MagicNumbers> for MagicTable {
+    type Error = String;
+
+    fn try_from(value: MagicNumbers) -> Result<Self, Self::Error> {
+        MagicTable::from_magics(value.magics).map_err(|e| e.to_string())
+    }
+}
+
+impl From<MagicTable> for MagicNumbers {
+    fn from(value: MagicTable) -> Self {
+        MagicNumbers {
+            magics: value.magics.into_owned(),
+        }
+    }
+}
+
+impl TryFrom<HashMagicTable> for MagicTable {
+    type Error = VikingChessError;
+
+    fn try_from(item: HashMagicTable) -> Result<Self, Self::Error> {
+        let geometry = geometry_of(&item.magics)?;
+        if item.moves.len() != item.magics.len() {
+            let reason = format!("expected {} move maps, found {}.", item.magics.len(), item.moves.len());
+            return Err(VikingChessError::MagicTableLoad(reason));
+        }
+
+        let layout = geometry.layout::<Mask>()?;
+        let shifts = MagicTable::shifts(layout);
+        let mut blockers = Vec::with_capacity(item.magics.len());
+        let mut offsets = Vec::with_capacity(item.magics.len());
+        let mut moves = Vec::new();
+
+        for (index, moves_map) in item.moves.iter().enumerate() {
+            let square = geometry.square(index)?;
+            let size = 1 << shifts[index];
+            let offset = moves.len();
+            moves.resize(offset + size, Mask(0));
+
+            for (key, &legal_moves) in moves_map {
+                let Some(slot) = usize::try_from(key.0).ok().filter(|&slot| slot < size) else {
+                    let reason = format!("key {} of {square:?} is out of range.", key.0);
+                    return Err(VikingChessError::MagicTableLoad(reason));
+                };
+
+                moves[offset + slot] = legal_moves;
+            }
+
+            blockers.push(layout.blockers(square));
+            offsets.push(offset);
+        }
+
+        let table = MagicTable {
+            geometry,
+            shifts: shifts.into(),
+            magics: item.magics.into(),
+            blockers: blockers.into(),
+            offsets: offsets.into(),
+            moves: moves.into(),
+        };
+
+        table.verify()?;
+        Ok(table)
+    }
+}
+
+impl From<&MagicTable> for HashMagicTable {
+    fn from(item: &MagicTable) -> Self {
+        let mut moves = Vec::with_capacity(item.magics.len());
+
+        for (index, &magic) in item.magics.iter().enumerate() {
+            let shift = item.shifts[index];
+            let moves_map = subsets(item.blockers[index])
+                .map(|blockers| MagicTable::index(blockers, magic, shift))
+                .map(|key| (Mask(key as u128), item.moves[item.offsets[index] + key]))
+                .collect();
 
-    #[rustfmt::skip]
-    pub const SHIFTS: [u32; Bitboard::TOTAL_SQUARES] = [
-        14, 13, 13, 13, 13, 13, 13, 13, 14,
-        13, 12, 12, 12, 12, 12, 12, 12, 13,
-        13, 12, 12, 12, 12, 12, 12, 12, 13,
-        13, 12, 12, 12, 12, 12, 12, 12, 13,
-        13, 12, 12, 12, 12, 12, 12, 12, 13,
-        13, 12, 12, 12, 12, 12, 12, 12, 13,
-        13, 12, 12, 12, 12, 12, 12, 12, 13,
-        13, 12, 12, 12, 12, 12, 12, 12, 13,
-        14, 13, 13, 13, 13, 13, 13, 13, 14,
-    ];
-}
-
-impl From<Vec<(Mask, HashMap<Mask, Mask>)>> for MagicTable {
-    fn from(item: Vec<(Mask, HashMap<Mask, Mask>)>) -> Self {
-        let mut magics = Vec::with_capacity(item.len());
-        let mut moves = Vec::with_capacity(item.len());
-
-        for (magic, moves_map) in item {
-            magics.push(magic);
             moves.push(moves_map);
         }
 
-        MagicTable {
-            magics,
+        HashMagicTable {
+            geometry: item.geometry,
+            magics: item.magics.to_vec(),
+            blockers: item.blockers.to_vec(),
             moves,
         }
     }
 }
 
-impl TryFrom<String> for MagicTable {
+impl TryFrom<HashMagicMoves> for HashMagicTable {
+    type Error = VikingChessError;
+
+    fn try_from(value: HashMagicMoves) -> Result<Self, Self::Error> {
+        let geometry = geometry_of(&value.magics)?;
+        let layout = geometry.layout::<Mask>()?;
+
+        Ok(HashMagicTable {
+            geometry,
+            magics: value.magics,
+            blockers: geometry.squares().map(|square| layout.blockers(square)).collect(),
+            moves: value.moves,
+        })
+    }
+}
+
+impl From<HashMagicTable> for HashMagicMoves {
+    fn from(value: HashMagicTable) -> Self {
+        HashMagicMoves {
+            magics: value.magics,
+            moves: value.moves,
+        }
+    }
+}
+
+impl TryFrom<String> for HashMagicTable {
     type Error = SpannedError;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
         ron::from_str(&value)
     }
 }
+
+impl TryFrom<String> for MagicTable {
+    type Error = VikingChessError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        ron::from_str(&value).or_else(|e| match HashMagicTable::try_from(value) {
+            Ok(hash_table) => MagicTable::try_from(hash_table),
+            Err(_) => Err(VikingChessError::MagicTableLoad(e.to_string())),
+        })
+    }
+}
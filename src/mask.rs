@@ -1,4 +1,6 @@
 use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
 use std::ops::BitAnd;
 use std::ops::BitAndAssign;
 use std::ops::BitOr;
@@ -13,14 +15,181 @@ use serde::Deserialize;
 use serde::Serialize;
 use serde::Serializer;
 
-#[derive(Default, Debug, PartialEq, PartialOrd, Eq, Hash, Clone, Copy)]
+use crate::bit_set::BitSet;
+use crate::direction::Direction;
+use crate::geometry::Geometry;
+use crate::geometry::Layout;
+use crate::magics::MagicTable;
+use crate::square::Square;
+use crate::symmetry::Symmetry;
+
+#[derive(Default, Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Clone, Copy)]
+#[repr(transparent)]
 pub struct Mask(pub u128);
 
 impl Mask {
-    pub const CORNER_MASK: Mask = Mask(1 << 0 | 1 << (9 - 1) | 1 << (9 * 8) | 1 << (9 * 9 - 1));
-    pub const THRONE_MASK: Mask = Mask(1 << (9 * 4 + 4));
+    pub fn north(self, layout: &Layout) -> Mask {
+        self.shift(Direction::North, layout)
+    }
+
+    pub fn east(self, layout: &Layout) -> Mask {
+        self.shift(Direction::East, layout)
+    }
+
+    pub fn south(self, layout: &Layout) -> Mask {
+        self.shift(Direction::South, layout)
+    }
+
+    pub fn west(self, layout: &Layout) -> Mask {
+        self.shift(Direction::West, layout)
+    }
+
+    pub fn shift(self, direction: Direction, layout: &Layout) -> Mask {
+        layout.shift(self, direction)
+    }
+
+    pub fn file(col: u8, layout: &Layout) -> Mask {
+        layout.file(col)
+    }
+
+    pub fn rank(row: u8, layout: &Layout) -> Mask {
+        layout.rank(row)
+    }
+
+    pub fn ray(square: Square, direction: Direction, layout: &Layout) -> Mask {
+        layout.ray(square, direction)
+    }
+
+    pub fn between(a: Square, b: Square, layout: &Layout) -> Mask {
+        layout.between(a, b)
+    }
+
+    pub fn line(a: Square, b: Square, layout: &Layout) -> Mask {
+        layout.line(a, b)
+    }
+
+    pub fn flood_fill(seed: Mask, passable: Mask, layout: &Layout) -> Mask {
+        layout.flood_fill(seed, passable)
+    }
+
+    pub fn flip_vertical(self, layout: &Layout) -> Mask {
+        layout.flip_vertical(self)
+    }
+
+    pub fn flip_horizontal(self, layout: &Layout) -> Mask {
+        layout.flip_horizontal(self)
+    }
+
+    pub fn transpose(self, layout: &Layout) -> Mask {
+        layout.transpose(self)
+    }
+
+    pub fn rotate90(self, layout: &Layout) -> Mask {
+        layout.rotate90(self)
+    }
+
+    pub fn transformed(self, symmetry: Symmetry, layout: &Layout) -> Mask {
+        layout.transform(self, symmetry)
+    }
 }
 
+impl BitSet for Mask {
+    const BITS: usize = u128::BITS as usize;
+    const EMPTY: Mask = Mask(0);
+
+    fn bit(index: usize) -> Self {
+        Mask(1u128.checked_shl(index as u32).unwrap_or(0))
+    }
+
+    fn low_bits(count: usize) -> Self {
+        match count >= Self::BITS {
+            true => Mask(u128::MAX),
+            false => Mask((1 << count) - 1),
+        }
+    }
+
+    fn get(self, index: usize) -> bool {
+        index < Self::BITS && (self.0 >> index) & 1 == 1
+    }
+
+    fn shift_left(self, bits: usize) -> Self {
+        Mask(self.0.checked_shl(bits as u32).unwrap_or(0))
+    }
+
+    fn shift_right(self, bits: usize) -> Self {
+        Mask(self.0.checked_shr(bits as u32).unwrap_or(0))
+    }
+
+    fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    fn lsb(self) -> Option<usize> {
+        match self.is_empty() {
+            true => None,
+            false => Some(self.0.trailing_zeros() as usize),
+        }
+    }
+
+    fn pop_lsb(&mut self) -> Option<usize> {
+        let index = self.lsb()?;
+        self.0 &= self.0 - 1;
+        Some(index)
+    }
+
+    fn magic_moves(magic_table: &MagicTable, square: Square, occupied: Self) -> Option<Self> {
+        Some(magic_table.moves(square, occupied))
+    }
+}
+
+pub struct MaskDisplay<M: BitSet = Mask> {
+    mask: M,
+    geometry: Geometry,
+}
+
+impl<M: BitSet> MaskDisplay<M> {
+    pub fn new(mask: M, geometry: Geometry) -> Self {
+        Self {
+            mask,
+            geometry,
+        }
+    }
+}
+
+pub struct MaskIter<M: BitSet = Mask> {
+    remaining: M,
+    geometry: Geometry,
+}
+
+impl<M: BitSet> MaskIter<M> {
+    pub fn new(remaining: M, geometry: Geometry) -> Self {
+        Self {
+            remaining,
+            geometry,
+        }
+    }
+}
+
+impl<M: BitSet> Iterator for MaskIter<M> {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.remaining.pop_lsb()?;
+        self.geometry.square(index).ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.remaining.count() as usize;
+        (count, Some(count))
+    }
+}
+
+impl<M: BitSet> ExactSizeIterator for MaskIter<M> {}
+
 impl Serialize for Mask {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -63,6 +232,30 @@ impl<'de> Deserialize<'de> for Mask {
     }
 }
 
+impl Display for Mask {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display(Geometry::TABLUT))
+    }
+}
+
+impl<M: BitSet> Display for MaskDisplay<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let length = self.geometry.length();
+        for row in 0..length {
+            for col in 0..length {
+                match self.mask.get(row * length + col) {
+                    true => write!(f, "1")?,
+                    false => write!(f, ".")?,
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl Deref for Mask {
     type Target = u128;
 
@@ -0,0 +1,32 @@
+use crate::piece::Piece;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WinReason {
+    KingEscaped,
+    KingCaptured,
+    NoLegalMoves,
+    Encircled,
+    EdgeFort,
+    Repetition,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DrawReason {
+    Repetition,
+    MoveLimit,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GameResult {
+    Win { winner: Piece, reason: WinReason },
+    Draw { reason: DrawReason },
+}
+
+impl GameResult {
+    pub fn winner(&self) -> Option<Piece> {
+        match self {
+            GameResult::Win { winner, .. } => Some(*winner),
+            GameResult::Draw { .. } => None,
+        }
+    }
+}
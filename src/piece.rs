@@ -1,5 +1,8 @@
+use crate::VikingChessError;
+use crate::VikingChessResult;
+
 #[repr(usize)]
-#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Hash, Clone, Copy)]
 pub enum Piece {
     King = 0,
     Defender = 1,
@@ -9,23 +12,36 @@ pub enum Piece {
 
 impl Piece {
     pub const PIECES: [char; 3] = ['A', 'D', 'K'];
-    pub fn opposite(&self) -> Piece {
+    pub const ALL: [Piece; 3] = [Piece::Attacker, Piece::Defender, Piece::King];
+
+    pub fn opposite(&self) -> VikingChessResult<Piece> {
         match self {
-            Piece::King => Piece::Attacker,
-            Piece::Defender => Piece::Attacker,
-            Piece::Attacker => Piece::Defender,
-            _ => panic!("Invalid piece!"),
+            Piece::King => Ok(Piece::Attacker),
+            Piece::Defender => Ok(Piece::Attacker),
+            Piece::Attacker => Ok(Piece::Defender),
+            Piece::Length => Err(VikingChessError::InvalidPieceKind(*self)),
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            Piece::Attacker => 'A',
+            Piece::Defender => 'D',
+            Piece::King => 'K',
+            _ => '?',
         }
     }
 }
 
-impl From<char> for Piece {
-    fn from(value: char) -> Self {
+impl TryFrom<char> for Piece {
+    type Error = VikingChessError;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
         match value {
-            'A' => Piece::Attacker,
-            'D' => Piece::Defender,
-            'K' => Piece::King,
-            _ => panic!("Failure to convert {value} to Piece."),
+            'A' => Ok(Piece::Attacker),
+            'D' => Ok(Piece::Defender),
+            'K' => Ok(Piece::King),
+            _ => Err(VikingChessError::InvalidPiece(value)),
         }
     }
 }
@@ -0,0 +1,128 @@
+use crate::bit_set::BitSet;
+use crate::geometry::Layout;
+use crate::piece::Piece;
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum KingCapture {
+    #[default]
+    Surrounded,
+    SurroundedOnEdge,
+    Custodial,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Escape {
+    #[default]
+    Corner,
+    Edge,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Repetition {
+    #[default]
+    Draw,
+    AttackersWin,
+    DefendersWin,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RuleSet {
+    pub throne_passable: bool,
+    pub throne_hostile_to_defenders: bool,
+    pub armed_king: bool,
+    pub king_reenters_throne: bool,
+    pub escape: Escape,
+    pub king_capture: KingCapture,
+    pub shieldwall: bool,
+    pub edge_fort: bool,
+    pub repetition: Repetition,
+}
+
+impl RuleSet {
+    pub const fn tablut() -> Self {
+        Self {
+            throne_passable: true,
+            throne_hostile_to_defenders: true,
+            armed_king: true,
+            king_reenters_throne: false,
+            escape: Escape::Corner,
+            king_capture: KingCapture::Surrounded,
+            shieldwall: false,
+            edge_fort: false,
+            repetition: Repetition::Draw,
+        }
+    }
+
+    pub const fn copenhagen() -> Self {
+        Self {
+            throne_passable: true,
+            throne_hostile_to_defenders: true,
+            armed_king: true,
+            king_reenters_throne: true,
+            escape: Escape::Corner,
+            king_capture: KingCapture::Surrounded,
+            shieldwall: true,
+            edge_fort: true,
+            repetition: Repetition::AttackersWin,
+        }
+    }
+
+    pub const fn linnaeus() -> Self {
+        Self {
+            throne_passable: true,
+            throne_hostile_to_defenders: true,
+            armed_king: true,
+            king_reenters_throne: false,
+            escape: Escape::Edge,
+            king_capture: KingCapture::Surrounded,
+            shieldwall: false,
+            edge_fort: false,
+            repetition: Repetition::Draw,
+        }
+    }
+
+    pub const fn fetlar() -> Self {
+        Self {
+            throne_passable: false,
+            throne_hostile_to_defenders: false,
+            armed_king: true,
+            king_reenters_throne: true,
+            escape: Escape::Corner,
+            king_capture: KingCapture::Surrounded,
+            shieldwall: false,
+            edge_fort: false,
+            repetition: Repetition::Draw,
+        }
+    }
+
+    pub fn escape_squares<M: BitSet>(&self, layout: &Layout<M>) -> M {
+        match self.escape {
+            Escape::Corner => layout.corners(),
+            Escape::Edge => layout.edges(),
+        }
+    }
+
+    pub fn hostile_corners<M: BitSet>(&self, layout: &Layout<M>) -> M {
+        match self.escape {
+            Escape::Corner => layout.corners(),
+            Escape::Edge => M::EMPTY,
+        }
+    }
+
+    pub fn throne_closed(&self, piece: Piece) -> bool {
+        piece != Piece::King || !self.king_reenters_throne
+    }
+
+    pub fn restricted<M: BitSet>(&self, piece: Piece, layout: &Layout<M>) -> M {
+        match piece {
+            Piece::King => M::EMPTY,
+            _ => self.hostile_corners(layout),
+        }
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::tablut()
+    }
+}
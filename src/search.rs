@@ -0,0 +1,301 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::action::Action;
+use crate::bit_set::BitSet;
+use crate::board::Board;
+use crate::magics::MagicTable;
+use crate::piece::Piece;
+use crate::rules::Escape;
+use crate::transposition::Bound;
+use crate::transposition::TranspositionEntry;
+use crate::transposition::TranspositionTable;
+
+pub const MATE_SCORE: i32 = 1_000_000;
+pub const INFINITY: i32 = MATE_SCORE + 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub depth: usize,
+    pub nodes: Option<u64>,
+    pub time: Option<Duration>,
+}
+
+impl SearchLimits {
+    pub const MAX_DEPTH: usize = 64;
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        Self {
+            depth: Self::MAX_DEPTH,
+            nodes: None,
+            time: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    pub best_action: Option<Action>,
+    pub score: i32,
+    pub depth: usize,
+    pub nodes: u64,
+    pub pv: Vec<Action>,
+}
+
+pub struct Search<'a> {
+    limits: SearchLimits,
+    magic_table: Option<&'a MagicTable>,
+    transposition_table: Option<&'a TranspositionTable>,
+    stop: Arc<AtomicBool>,
+    nodes: u64,
+    start: Instant,
+    stopped: bool,
+}
+
+impl<'a> Search<'a> {
+    const TIME_CHECK_INTERVAL: u64 = 2048;
+
+    pub fn new(limits: SearchLimits, magic_table: Option<&'a MagicTable>) -> Self {
+        Self {
+            limits,
+            magic_table,
+            transposition_table: None,
+            stop: Arc::new(AtomicBool::new(false)),
+            nodes: 0,
+            start: Instant::now(),
+            stopped: false,
+        }
+    }
+
+    pub fn with_transposition_table(mut self, transposition_table: &'a TranspositionTable) -> Self {
+        self.transposition_table = Some(transposition_table);
+        self
+    }
+
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop)
+    }
+
+    pub fn run(&mut self, board: &mut Board) -> SearchResult {
+        self.nodes = 0;
+        self.start = Instant::now();
+        self.stopped = false;
+
+        if let Some(transposition_table) = self.transposition_table {
+            transposition_table.new_search();
+        }
+
+        let mut result = SearchResult {
+            best_action: board.legal_actions(self.magic_table).first().copied(),
+            score: 0,
+            depth: 0,
+            nodes: 0,
+            pv: Vec::new(),
+        };
+
+        for depth in 1..=self.limits.depth.min(SearchLimits::MAX_DEPTH) {
+            let mut pv = Vec::new();
+            let score = self.negamax(board, depth, 0, -INFINITY, INFINITY, &result.pv, &mut pv);
+
+            if self.stopped && (depth > 1 || pv.is_empty()) {
+                break;
+            }
+
+            result = SearchResult {
+                best_action: pv.first().copied(),
+                score,
+                depth,
+                nodes: self.nodes,
+                pv,
+            };
+
+            if self.stopped || score.abs() >= MATE_SCORE - SearchLimits::MAX_DEPTH as i32 {
+                break;
+            }
+        }
+
+        result.nodes = self.nodes;
+        self.stop.store(false, Ordering::Relaxed);
+        result
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if self.stopped {
+            return true;
+        }
+
+        let out_of_nodes = self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes);
+        let out_of_time = self.nodes.is_multiple_of(Self::TIME_CHECK_INTERVAL)
+            && self.limits.time.is_some_and(|time| self.start.elapsed() >= time);
+
+        self.stopped = out_of_nodes || out_of_time || self.stop.load(Ordering::Relaxed);
+        self.stopped
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(
+        &mut self,
+        board: &mut Board,
+        depth: usize,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+        previous_pv: &[Action],
+        pv: &mut Vec<Action>,
+    ) -> i32 {
+        pv.clear();
+        self.nodes += 1;
+
+        if ply > 0 && self.should_stop() {
+            return 0;
+        } else if let Some(result) = board.outcome_with_magic_table(self.magic_table) {
+            return match result.winner() {
+                Some(winner) if winner == board.state.turn => MATE_SCORE - ply as i32,
+                Some(_) => -MATE_SCORE + ply as i32,
+                None => 0,
+            };
+        } else if depth == 0 {
+            return evaluate(board, self.magic_table);
+        }
+
+        let hash = board.state.zobrist_hash;
+        let entry = self.transposition_table.and_then(|table| table.probe(hash));
+        if let Some(entry) = entry
+            && ply > 0
+            && entry.depth as usize >= depth
+        {
+            let score = score_from_table(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return score,
+                Bound::Lower if score >= beta => return score,
+                Bound::Upper if score <= alpha => return score,
+                _ => {}
+            }
+        }
+
+        let mut actions = board.legal_actions(self.magic_table).to_vec();
+        if actions.is_empty() {
+            return -MATE_SCORE + ply as i32;
+        }
+
+        let position = previous_pv.first().and_then(|a| actions.iter().position(|b| a == b));
+        let hinted = entry.and_then(|e| e.best_action).and_then(|a| actions.iter().position(|&b| a == b));
+        if let Some(first) = position.or(hinted) {
+            actions[..=first].rotate_right(1);
+        }
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_action = None;
+        let mut line = Vec::new();
+        for (i, &action) in actions.iter().enumerate() {
+            if board.play_unchecked(action).is_err() {
+                continue;
+            }
+
+            let child_pv = match (i, position) {
+                (0, Some(_)) => &previous_pv[1..],
+                _ => &[],
+            };
+
+            let score = -self.negamax(board, depth - 1, ply + 1, -beta, -alpha, child_pv, &mut line);
+            let _ = board.undo();
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_action = Some(action);
+            }
+
+            if score > alpha {
+                alpha = score;
+                pv.clear();
+                pv.push(action);
+                pv.extend_from_slice(&line);
+            }
+
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if let Some(transposition_table) = self.transposition_table {
+            let bound = match best_score {
+                score if score <= original_alpha => Bound::Upper,
+                score if score >= beta => Bound::Lower,
+                _ => Bound::Exact,
+            };
+
+            let entry = TranspositionEntry {
+                depth: depth as u8,
+                score: score_to_table(best_score, ply),
+                bound,
+                best_action,
+            };
+
+            transposition_table.store(hash, entry);
+        }
+
+        best_score
+    }
+}
+
+fn score_to_table(score: i32, ply: usize) -> i32 {
+    match score {
+        score if score >= MATE_SCORE - SearchLimits::MAX_DEPTH as i32 => score + ply as i32,
+        score if score <= -MATE_SCORE + SearchLimits::MAX_DEPTH as i32 => score - ply as i32,
+        score => score,
+    }
+}
+
+fn score_from_table(score: i32, ply: usize) -> i32 {
+    match score {
+        score if score >= MATE_SCORE - SearchLimits::MAX_DEPTH as i32 => score - ply as i32,
+        score if score <= -MATE_SCORE + SearchLimits::MAX_DEPTH as i32 => score + ply as i32,
+        score => score,
+    }
+}
+
+pub fn evaluate(board: &Board, magic_table: Option<&MagicTable>) -> i32 {
+    const ATTACKER_VALUE: i32 = 100;
+    const DEFENDER_VALUE: i32 = 180;
+    const ESCAPE_DISTANCE_PENALTY: i32 = 12;
+    const SURROUNDED_PENALTY: i32 = 40;
+    const MOBILITY_BONUS: i32 = 2;
+
+    let bitboard = board.bitboard();
+    let geometry = board.geometry();
+    let king = bitboard[Piece::King];
+    let Some(king_square) = king.iter_squares(geometry).next() else {
+        return 0;
+    };
+
+    let last = geometry.length() as u8 - 1;
+    let row_distance = king_square.row.min(last - king_square.row) as i32;
+    let col_distance = king_square.col.min(last - king_square.col) as i32;
+    let surrounded = (king_square.adjacent_mask(board.layout()) & bitboard[Piece::Attacker]).count_ones() as i32;
+    let escape_distance = match board.rules().escape {
+        Escape::Corner => row_distance + col_distance,
+        Escape::Edge => row_distance.min(col_distance),
+    };
+
+    let mut score = bitboard[Piece::Defender].count_ones() as i32 * DEFENDER_VALUE
+        - bitboard[Piece::Attacker].count_ones() as i32 * ATTACKER_VALUE
+        - escape_distance * ESCAPE_DISTANCE_PENALTY
+        - surrounded * SURROUNDED_PENALTY;
+
+    if board.state.turn == Piece::Attacker {
+        score = -score;
+    }
+
+    let mobility = board.legal_action_count(magic_table) as i32;
+    score + mobility * MOBILITY_BONUS
+}
@@ -1,9 +1,13 @@
 use crate::action::Action;
+use crate::bit_set::BitSet;
+use crate::bitboard::GenericBitboard;
+use crate::mask::Mask;
 use crate::piece::Piece;
 
 #[derive(Clone, Copy)]
-pub struct State {
+pub struct State<M: BitSet = Mask> {
     pub zobrist_hash: u64,
     pub turn: Piece,
     pub action: Option<Action>,
+    pub captured: GenericBitboard<M>,
 }
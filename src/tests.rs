@@ -1,23 +1,62 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 
 use super::*;
 use crate::action::Action;
+use crate::action::ActionList;
+use crate::bit_set::BitSet;
 use crate::bitboard::Bitboard;
 use crate::board::Board;
+use crate::board::Board13;
+use crate::board::Board19;
+use crate::board::WideBoard;
+use crate::direction::Direction;
+use crate::error::Restriction;
+use crate::fen::FenError;
+use crate::fen::FenErrorKind;
+use crate::geometry::Geometry;
+use crate::geometry::Layout;
+use crate::magics::HashMagicTable;
+use crate::magics::MagicTable;
 use crate::mask::Mask;
+use crate::outcome::DrawReason;
+use crate::outcome::GameResult;
+use crate::outcome::WinReason;
 use crate::piece::Piece;
+use crate::rules::KingCapture;
+use crate::rules::Repetition;
+use crate::rules::RuleSet;
+use crate::search::MATE_SCORE;
+use crate::search::Search;
+use crate::search::SearchLimits;
 use crate::square::Square;
+use crate::symmetry::Symmetry;
+use crate::transposition::Bound;
+use crate::transposition::TranspositionEntry;
+use crate::transposition::TranspositionTable;
+use crate::wide_mask::Mask19;
+use crate::wide_mask::WideMask;
 use crate::zobrist::ZobristTable;
 
 #[test]
 fn bitboard_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
     let mut board = Bitboard::default();
 
     assert_eq!(board[Piece::King], Mask(0));
     assert_eq!(board[Piece::Defender], Mask(0));
     assert_eq!(board[Piece::Attacker], Mask(0));
 
-    board[Piece::King] |= Square::try_from((4, 4))?.mask();
+    board[Piece::King] |= Square::try_from((4, 4))?.mask(layout);
     assert_eq!(board[Piece::King], Mask(1 << 40));
     Ok(())
 }
@@ -31,16 +70,17 @@ fn bitboard_index_panic() {
 
 #[test]
 fn zobrist_hash_update_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
     let mut board = Board::new();
     let initial_hash = board.state.zobrist_hash;
 
-    let action = Action::new(Piece::Defender, 39.try_into()?, 30.try_into()?);
-    board.state.turn = Piece::Defender;
+    let action = Action::new(Piece::Defender, geometry.square(39)?, geometry.square(30)?);
+    board.set_turn(Piece::Defender);
     board.move_piece(action, None)?;
     assert_ne!(board.state.zobrist_hash, initial_hash);
 
-    let action = Action::new(Piece::Defender, 30.try_into()?, 39.try_into()?);
-    board.state.turn = Piece::Defender;
+    let action = Action::new(Piece::Defender, geometry.square(30)?, geometry.square(39)?);
+    board.set_turn(Piece::Defender);
     board.move_piece(action, None)?;
     assert_eq!(board.state.zobrist_hash, initial_hash);
     Ok(())
@@ -56,18 +96,21 @@ fn zobristkeys_no_dup() {
 }
 
 #[test]
-fn zobrist_table_index_test() {
+fn zobrist_piece_key_test() {
     let table = ZobristTable::new();
     let piece = Piece::Defender;
     let square = Square::new(2, 3);
-    let expected_index = piece as usize * Bitboard::TOTAL_SQUARES + square.index();
-    assert_eq!(table[(piece, square)], (*table)[expected_index]);
+
+    for geometry in Geometry::ALL {
+        let expected_index = piece as usize * geometry.total_squares() + square.index(geometry);
+        assert_eq!(table.piece_key(geometry, piece, square), (*table)[expected_index]);
+    }
 }
 
 #[test]
 fn square_from_usize_test() -> VikingChessResult<()> {
     let square_index = 15;
-    let square = Square::try_from(square_index)?;
+    let square = Geometry::TABLUT.square(square_index)?;
 
     assert_eq!(square.row, 1);
     assert_eq!(square.col, 6);
@@ -81,11 +124,34 @@ fn square_from_tuple_test() -> VikingChessResult<()> {
 
     assert_eq!(square.row, 1);
     assert_eq!(square.col, 4);
+
+    let last = Geometry::MAX_LENGTH as u8 - 1;
+    assert_eq!(Square::try_from((last, last))?, Square::new(last, last));
+    assert!(Square::try_from((last + 1, 0)).is_err());
+    assert!(Square::try_from((0, last + 1)).is_err());
+    Ok(())
+}
+
+#[test]
+fn bitboard_format_test() -> VikingChessResult<()> {
+    let bitboard = Bitboard::from_fen("3A3/3A3/3D3/AADKDAA/3D3/3A3/3A3")?;
+    assert_eq!(bitboard.to_string(), "...A...\n...A...\n...D...\nAADKDAA\n...D...\n...A...\n...A...\n");
+
+    let debug = format!("{bitboard:?}");
+    assert!(debug.contains("Mask King:\n.......\n.......\n.......\n...1...\n"));
+    assert!(debug.lines().filter(|line| !line.is_empty() && !line.ends_with(':')).all(|line| line.len() == 7));
+
+    let king = bitboard[Piece::King];
+    assert_eq!(king.display(Geometry::BRANDUBH).to_string(), ".......\n.......\n.......\n...1...\n.......\n.......\n.......\n");
+    assert_eq!(Mask(1 << 10).to_string(), ".........\n.1.......\n.........\n.........\n.........\n.........\n.........\n.........\n.........\n");
+    assert_eq!(ron::to_string(&Mask(1 << 10)).expect("Failed to serialize the mask."), "\"1024\"");
     Ok(())
 }
 
 #[test]
 fn bitboard_iter_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
     let mut bitboard = Bitboard::default();
     let squares = [
         Square::try_from((4, 4))?,
@@ -93,9 +159,9 @@ fn bitboard_iter_test() -> VikingChessResult<()> {
         Square::try_from((8, 8))?,
     ];
 
-    bitboard[Piece::King] |= squares[0].mask();
-    bitboard[Piece::Attacker] |= squares[1].mask();
-    bitboard[Piece::Defender] |= squares[2].mask();
+    bitboard[Piece::King] |= squares[0].mask(layout);
+    bitboard[Piece::Attacker] |= squares[1].mask(layout);
+    bitboard[Piece::Defender] |= squares[2].mask(layout);
 
     let mut iter = bitboard.iter();
     assert_eq!(iter.next(), Some((Piece::Attacker, squares[1])));
@@ -105,8 +171,70 @@ fn bitboard_iter_test() -> VikingChessResult<()> {
     Ok(())
 }
 
+#[test]
+fn mask_iter_squares_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let empty = Mask(0);
+    assert!(empty.is_empty());
+    assert_eq!(empty.count(), 0);
+    assert_eq!(empty.lsb(), None);
+    assert_eq!(empty.iter_squares(geometry).next(), None);
+
+    let mut mask = Square::try_from((4, 4))?.mask(layout) | Square::try_from((0, 0))?.mask(layout) | Square::try_from((8, 8))?.mask(layout);
+    assert!(!mask.is_empty());
+    assert_eq!(mask.count(), 3);
+    assert_eq!(mask.lsb(), Some(0));
+
+    let squares: Vec<_> = mask.iter_squares(geometry).collect();
+    assert_eq!(squares, [Square::new(0, 0), Square::new(4, 4), Square::new(8, 8)]);
+    assert_eq!(mask.iter_squares(geometry).len(), 3);
+
+    assert_eq!(mask.pop_lsb(), Some(0));
+    assert_eq!(mask.pop_lsb(), Some(40));
+    assert_eq!(mask.pop_lsb(), Some(80));
+    assert_eq!(mask.pop_lsb(), None);
+    assert!(mask.is_empty());
+
+    let mut rng = StdRng::seed_from_u64(23);
+    for geometry in Geometry::ALL.into_iter().filter(|geometry| geometry.fits::<Mask>()) {
+        let layout = geometry.layout::<Mask>()?;
+        for _ in 0..100 {
+            let mask = Mask(rng.random::<u128>() & rng.random::<u128>());
+            let expected: Vec<_> = geometry.squares().filter(|&square| layout.mask(square) & mask > Mask(0)).collect();
+            let squares: Vec<_> = mask.iter_squares(geometry).collect();
+
+            assert_eq!(squares, expected);
+            assert_eq!(mask.iter_squares(geometry).len(), expected.len());
+            assert_eq!((mask & layout.board()).count() as usize, expected.len());
+        }
+    }
+
+    let fens = PERFT_SUITE.iter().map(|(fen, _)| *fen).chain([Board::BRANDUBH_FEN, Board::HNEFATAFL_FEN]);
+    for fen in fens {
+        let board = Board::from_fen(fen)?;
+        let bitboard = board.bitboard();
+        let geometry = bitboard.geometry();
+        let layout = board.layout();
+        let expected: Vec<_> = geometry
+            .squares()
+            .filter_map(|square| {
+                let piece = Piece::ALL.into_iter().find(|&piece| bitboard[piece] & layout.mask(square) > Mask(0))?;
+                Some((piece, square))
+            })
+            .collect();
+
+        assert_eq!(bitboard.iter().collect::<Vec<_>>(), expected, "{fen}");
+        assert_eq!(bitboard.iter().len(), expected.len());
+    }
+
+    Ok(())
+}
+
 #[test]
 fn square_adjacent_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
     let squares = [
         Square::try_from((4, 4))?,
         Square::try_from((0, 0))?,
@@ -129,8 +257,8 @@ fn square_adjacent_test() -> VikingChessResult<()> {
 
     let iter = squares.iter().zip(adjacent_masks.iter().zip(interjacent_mask.iter()));
     for (square, (adjacent_mask, interjacent_mask)) in iter {
-        assert_eq!(&square.adjacent_mask(), adjacent_mask);
-        assert_eq!(&square.interjacent_mask(), interjacent_mask);
+        assert_eq!(&square.adjacent_mask(layout), adjacent_mask);
+        assert_eq!(&square.interjacent_mask(layout), interjacent_mask);
     }
 
     Ok(())
@@ -138,22 +266,24 @@ fn square_adjacent_test() -> VikingChessResult<()> {
 
 #[test]
 fn test_legal_moves_no_blockers() {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>().unwrap();
     let blockers = Mask(0);
     let start_square = Square::try_from((4, 4)).unwrap();
-    let legal_moves = Bitboard::legal_moves(start_square, blockers);
+    let legal_moves = layout.legal_moves(start_square, blockers);
 
     let mut expected_moves = Mask(0);
     for r in 5..9 {
-        expected_moves |= Square::try_from((4, r)).unwrap().mask();
+        expected_moves |= Square::try_from((4, r)).unwrap().mask(layout);
     }
     for r in 0..4 {
-        expected_moves |= Square::try_from((4, r)).unwrap().mask();
+        expected_moves |= Square::try_from((4, r)).unwrap().mask(layout);
     }
     for f in 5..9 {
-        expected_moves |= Square::try_from((f, 4)).unwrap().mask();
+        expected_moves |= Square::try_from((f, 4)).unwrap().mask(layout);
     }
     for f in 0..4 {
-        expected_moves |= Square::try_from((f, 4)).unwrap().mask();
+        expected_moves |= Square::try_from((f, 4)).unwrap().mask(layout);
     }
 
     assert_eq!(legal_moves, expected_moves, "No blockers on a central square");
@@ -162,23 +292,25 @@ fn test_legal_moves_no_blockers() {
 
 #[test]
 fn test_legal_moves_with_blocker_up() {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>().unwrap();
     let blocker_square = Square::try_from((4, 6)).unwrap();
-    let blockers = blocker_square.mask();
+    let blockers = blocker_square.mask(layout);
     let start_square = Square::try_from((4, 4)).unwrap();
-    let legal_moves = Bitboard::legal_moves(start_square, blockers);
+    let legal_moves = layout.legal_moves(start_square, blockers);
 
     let mut expected_moves = Mask(0);
     for r in 5..6 {
-        expected_moves |= Square::try_from((4, r)).unwrap().mask();
+        expected_moves |= Square::try_from((4, r)).unwrap().mask(layout);
     }
     for r in 0..4 {
-        expected_moves |= Square::try_from((4, r)).unwrap().mask();
+        expected_moves |= Square::try_from((4, r)).unwrap().mask(layout);
     }
     for f in 5..9 {
-        expected_moves |= Square::try_from((f, 4)).unwrap().mask();
+        expected_moves |= Square::try_from((f, 4)).unwrap().mask(layout);
     }
     for f in 0..4 {
-        expected_moves |= Square::try_from((f, 4)).unwrap().mask();
+        expected_moves |= Square::try_from((f, 4)).unwrap().mask(layout);
     }
 
     assert_eq!(legal_moves, expected_moves, "Blocked by a piece on row 6");
@@ -187,20 +319,22 @@ fn test_legal_moves_with_blocker_up() {
 
 #[test]
 fn test_legal_moves_with_multiple_blockers() {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>().unwrap();
     let blocker1_square = Square::try_from((4, 6)).unwrap();
     let blocker2_square = Square::try_from((2, 4)).unwrap();
-    let blockers = blocker1_square.mask() | blocker2_square.mask();
+    let blockers = blocker1_square.mask(layout) | blocker2_square.mask(layout);
     let start_square = Square::try_from((4, 4)).unwrap();
-    let legal_moves = Bitboard::legal_moves(start_square, blockers);
+    let legal_moves = layout.legal_moves(start_square, blockers);
 
     let mut expected_moves = Mask(0);
-    expected_moves |= Square::try_from((3, 4)).unwrap().mask();
-    expected_moves |= Square::try_from((4, 5)).unwrap().mask();
+    expected_moves |= Square::try_from((3, 4)).unwrap().mask(layout);
+    expected_moves |= Square::try_from((4, 5)).unwrap().mask(layout);
     for r in 0..4 {
-        expected_moves |= Square::try_from((4, r)).unwrap().mask();
+        expected_moves |= Square::try_from((4, r)).unwrap().mask(layout);
     }
     for f in 5..9 {
-        expected_moves |= Square::try_from((f, 4)).unwrap().mask();
+        expected_moves |= Square::try_from((f, 4)).unwrap().mask(layout);
     }
 
     assert_eq!(legal_moves, expected_moves, "Blocked by two pieces");
@@ -209,16 +343,18 @@ fn test_legal_moves_with_multiple_blockers() {
 
 #[test]
 fn test_legal_moves_edge_case_corner() {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>().unwrap();
     let blockers = Mask(0);
     let start_square = Square::try_from((0, 0)).unwrap();
-    let legal_moves = Bitboard::legal_moves(start_square, blockers);
+    let legal_moves = layout.legal_moves(start_square, blockers);
 
     let mut expected_moves = Mask(0);
     for f in 1..9 {
-        expected_moves |= Square::try_from((f, 0)).unwrap().mask();
+        expected_moves |= Square::try_from((f, 0)).unwrap().mask(layout);
     }
     for r in 1..9 {
-        expected_moves |= Square::try_from((0, r)).unwrap().mask();
+        expected_moves |= Square::try_from((0, r)).unwrap().mask(layout);
     }
 
 
@@ -228,21 +364,1715 @@ fn test_legal_moves_edge_case_corner() {
 
 #[test]
 fn test_legal_moves_edge_case_side() {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>().unwrap();
     let blockers = Mask(0);
     let start_square = Square::try_from((0, 4)).unwrap();
-    let legal_moves = Bitboard::legal_moves(start_square, blockers);
+    let legal_moves = layout.legal_moves(start_square, blockers);
 
     let mut expected_moves = Mask(0);
     for f in 1..9 {
-        expected_moves |= Square::try_from((f, 4)).unwrap().mask();
+        expected_moves |= Square::try_from((f, 4)).unwrap().mask(layout);
     }
     for r in 0..4 {
-        expected_moves |= Square::try_from((0, r)).unwrap().mask();
+        expected_moves |= Square::try_from((0, r)).unwrap().mask(layout);
     }
     for r in 5..9 {
-        expected_moves |= Square::try_from((0, r)).unwrap().mask();
+        expected_moves |= Square::try_from((0, r)).unwrap().mask(layout);
     }
 
     assert_eq!(legal_moves, expected_moves, "No blockers on a side square");
     assert_eq!(legal_moves.0.count_ones(), 16, "Expected 16 legal moves");
 }
+
+#[test]
+fn custodial_capture_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let mut board = Board::from_fen("9/9/2AD4A/9/9/9/4K4/9/9 B")?;
+    let action = Action::new(Piece::Attacker, Square::new(2, 8), Square::new(2, 4));
+    board.move_piece(action, None)?;
+
+    assert_eq!(board.state.captured[Piece::Defender], Square::new(2, 3).mask(layout));
+    assert_eq!(board.bitboard().to_string(), ".........\n.........\n..A.A....\n.........\n.........\n.........\n....K....\n.........\n.........\n");
+    Ok(())
+}
+
+#[test]
+fn custodial_capture_double_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let mut board = Board::from_fen("9/9/1AD1DA3/9/9/9/3A5/9/4K4 B")?;
+    let action = Action::new(Piece::Attacker, Square::new(6, 3), Square::new(2, 3));
+    board.move_piece(action, None)?;
+
+    let expected = Square::new(2, 2).mask(layout) | Square::new(2, 4).mask(layout);
+    assert_eq!(board.state.captured[Piece::Defender], expected);
+    Ok(())
+}
+
+#[test]
+fn custodial_capture_hostile_squares_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let mut board = Board::from_fen("1D7/9/9/2A6/9/9/4K4/9/9 B")?;
+    let action = Action::new(Piece::Attacker, Square::new(3, 2), Square::new(0, 2));
+    board.move_piece(action, None)?;
+    assert_eq!(board.state.captured[Piece::Defender], Square::new(0, 1).mask(layout));
+
+    let mut board = Board::from_fen("4K4/9/9/9/3D5/9/9/9/2A6 B")?;
+    let action = Action::new(Piece::Attacker, Square::new(8, 2), Square::new(4, 2));
+    board.move_piece(action, None)?;
+    assert_eq!(board.state.captured[Piece::Defender], Square::new(4, 3).mask(layout));
+    Ok(())
+}
+
+#[test]
+fn custodial_capture_armed_king_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let mut board = Board::from_fen("9/9/2KA4D/9/9/9/9/9/9 W")?;
+    let action = Action::new(Piece::Defender, Square::new(2, 8), Square::new(2, 4));
+    board.move_piece(action, None)?;
+
+    assert_eq!(board.state.captured[Piece::Attacker], Square::new(2, 3).mask(layout));
+    Ok(())
+}
+
+#[test]
+fn custodial_capture_moving_into_sandwich_test() -> VikingChessResult<()> {
+    let mut board = Board::from_fen("9/9/2D1D4/9/9/3A5/9/4K4/9 B")?;
+    let action = Action::new(Piece::Attacker, Square::new(5, 3), Square::new(2, 3));
+    board.move_piece(action, None)?;
+
+    assert_eq!(board.state.captured, Bitboard::default());
+    assert_eq!(board.iter_bitboard().count(), 4);
+    Ok(())
+}
+
+#[test]
+fn king_capture_four_sided_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let mut board = Board::from_fen("9/4A4/3AKA3/8A/9/9/9/9/9 B")?;
+    let action = Action::new(Piece::Attacker, Square::new(3, 8), Square::new(3, 4));
+    board.move_piece(action, None)?;
+
+    assert_eq!(board.state.captured[Piece::King], Square::new(2, 4).mask(layout));
+    assert!(board.is_winner(Piece::Attacker));
+
+    let mut board = Board::from_fen("9/9/3AKA3/8A/9/9/9/9/9 B")?;
+    board.move_piece(action, None)?;
+
+    assert_eq!(board.state.captured[Piece::King], Mask(0));
+    assert!(!board.is_winner(Piece::Attacker));
+    Ok(())
+}
+
+#[test]
+fn king_capture_throne_adjacent_test() -> VikingChessResult<()> {
+    let mut board = Board::from_fen("9/9/4A4/3AK3A/9/9/9/9/9 B")?;
+    let action = Action::new(Piece::Attacker, Square::new(3, 8), Square::new(3, 5));
+    board.move_piece(action, None)?;
+
+    assert!(board.is_winner(Piece::Attacker));
+
+    let mut board = Board::from_fen("9/9/4A4/3AK3A/9/9/9/9/9 B")?;
+    board.set_king_capture(KingCapture::Custodial);
+    board.move_piece(action, None)?;
+
+    assert!(board.is_winner(Piece::Attacker));
+    Ok(())
+}
+
+#[test]
+fn king_capture_edge_test() -> VikingChessResult<()> {
+    let action = Action::new(Piece::Attacker, Square::new(1, 8), Square::new(1, 4));
+
+    let mut board = Board::from_fen("3AKA3/8A/9/9/9/9/9/9/9 B")?;
+    board.move_piece(action, None)?;
+    assert!(board.bitboard()[Piece::King] > Mask(0));
+    assert_eq!(board.outcome().map(|result| result.winner()), Some(Some(Piece::Attacker)));
+    assert!(matches!(board.outcome(), Some(GameResult::Win { reason: WinReason::NoLegalMoves, .. })));
+
+    let mut board = Board::from_fen("3AKA3/8A/9/9/9/9/9/9/9 B")?;
+    board.set_king_capture(KingCapture::SurroundedOnEdge);
+    board.move_piece(action, None)?;
+    assert!(board.is_winner(Piece::Attacker));
+    Ok(())
+}
+
+#[test]
+fn king_capture_custodial_test() -> VikingChessResult<()> {
+    let action = Action::new(Piece::Attacker, Square::new(6, 5), Square::new(2, 5));
+
+    let mut board = Board::from_fen("9/9/3AK4/9/9/9/5A3/9/9 B")?;
+    board.move_piece(action, None)?;
+    assert!(!board.is_winner(Piece::Attacker));
+
+    let mut board = Board::from_fen("9/9/3AK4/9/9/9/5A3/9/9 B")?;
+    board.set_king_capture(KingCapture::Custodial);
+    board.move_piece(action, None)?;
+    assert!(board.is_winner(Piece::Attacker));
+
+    let mut board = Board::from_fen("9/9/9/3AK4/9/9/5A3/9/9 B")?;
+    board.set_king_capture(KingCapture::Custodial);
+    board.move_piece(Action::new(Piece::Attacker, Square::new(6, 5), Square::new(3, 5)), None)?;
+    assert!(!board.is_winner(Piece::Attacker));
+    Ok(())
+}
+
+fn random_move(board: &mut Board, rng: &mut StdRng) -> bool {
+    let geometry = board.geometry();
+    let layout = board.layout();
+    let Ok(turn_mask) = board.turn_mask() else {
+        return false;
+    };
+
+    let pieces: Vec<_> = board.iter_bitboard().filter(|(_, s)| s.mask(layout) & turn_mask > Mask(0)).collect();
+
+    if pieces.is_empty() {
+        return false;
+    }
+
+    for _ in 0..256 {
+        let (piece, from) = pieces[rng.random_range(0..pieces.len())];
+        let to = geometry.square(rng.random_range(0..geometry.total_squares())).unwrap();
+        if board.move_piece(Action::new(piece, from, to), None).is_ok() {
+            return true;
+        }
+    }
+
+    false
+}
+
+#[test]
+fn undo_restores_capture_test() -> VikingChessResult<()> {
+    let mut board = Board::from_fen("9/9/1AD1DA3/9/9/9/3A5/9/4K4 B")?;
+    let bitboard = *board.bitboard();
+    let hash = board.state.zobrist_hash;
+
+    let action = Action::new(Piece::Attacker, Square::new(6, 3), Square::new(2, 3));
+    board.move_piece(action, None)?;
+    assert_ne!(board.bitboard(), &bitboard);
+
+    assert_eq!(board.undo()?, action);
+    assert_eq!(board.bitboard(), &bitboard);
+    assert_eq!(board.state.zobrist_hash, hash);
+    assert_eq!(board.state.turn, Piece::Attacker);
+    assert!(board.undo().is_err());
+    Ok(())
+}
+
+#[test]
+fn undo_random_sequences_test() -> VikingChessResult<()> {
+    let mut rng = StdRng::seed_from_u64(0x7AF1);
+
+    for _ in 0..32 {
+        let mut board = Board::new();
+        let mut snapshots = vec![(*board.bitboard(), board.state.zobrist_hash, board.state.turn)];
+
+        for _ in 0..rng.random_range(1..64) {
+            if !random_move(&mut board, &mut rng) {
+                break;
+            }
+
+            snapshots.push((*board.bitboard(), board.state.zobrist_hash, board.state.turn));
+        }
+
+        let undone = rng.random_range(0..snapshots.len());
+        board.undo_n(undone)?;
+        snapshots.truncate(snapshots.len() - undone);
+        assert_eq!(snapshots.last(), Some(&(*board.bitboard(), board.state.zobrist_hash, board.state.turn)));
+
+        while let Some(snapshot) = snapshots.pop() {
+            assert_eq!(snapshot, (*board.bitboard(), board.state.zobrist_hash, board.state.turn));
+            if !snapshots.is_empty() {
+                board.undo()?;
+            }
+        }
+
+        assert!(board.undo().is_err());
+        assert!(board.undo_n(1).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn legal_actions_restrictions_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let board = Board::from_fen("2D6/9/9/9/4K4/9/9/9/9 W")?;
+    let actions = board.legal_actions(None);
+
+    assert_eq!(actions.len(), 30);
+    assert_eq!(board.legal_action_count(None), 30);
+    assert!(actions.iter().all(|a| a.to.mask(layout) & (layout.corners() | layout.throne()) == Mask(0)));
+    assert_eq!(actions.iter().filter(|a| a.piece == Piece::King).count(), 16);
+
+    let board = Board::from_fen("K8/9/9/9/9/9/9/9/7A1 B")?;
+    assert_eq!(board.legal_action_count(None), 14);
+    Ok(())
+}
+
+#[test]
+fn legal_actions_match_move_piece_test() -> VikingChessResult<()> {
+    let mut rng = StdRng::seed_from_u64(0x1E6A);
+    let mut board = Board::new();
+    let geometry = board.geometry();
+    let layout = board.layout();
+
+    for _ in 0..24 {
+        let actions = board.legal_actions(None);
+        assert_eq!(actions.len(), board.legal_action_count(None));
+
+        let turn_mask = board.turn_mask()?;
+        let pieces: Vec<_> = board.iter_bitboard().filter(|(_, s)| s.mask(layout) & turn_mask > Mask(0)).collect();
+        let mut accepted = 0;
+        for &(piece, from) in &pieces {
+            for index in 0..geometry.total_squares() {
+                let action = Action::new(piece, from, geometry.square(index)?);
+                if board.move_piece(action, None).is_ok() {
+                    assert!(actions.contains(&action));
+                    board.undo()?;
+                    accepted += 1;
+                }
+            }
+        }
+
+        assert_eq!(accepted, actions.len());
+        if !random_move(&mut board, &mut rng) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+// Counts were produced by `Board::perft` itself, so they only guard against regressions. The depth 1 and 2
+// counts are re-derived by `perft_brute_force_test`, which replays every square pair through `move_piece`.
+const PERFT_SUITE: [(&str, [u64; 3]); 13] = [
+    (Board::STARTING_FEN, [72, 3944, 285728]),
+    ("3AAA3/4A4/4D4/A3D3A/AADDKDDAA/A3D3A/4D4/4A4/3AAA3 W", [56, 3968, 225224]),
+    ("3AAA3/4A4/4D4/A3D3A/AADD1DDAA/A3DK2A/4D4/4A4/3AAA3 B", [70, 3704, 260290]),
+    ("9/9/1AD1DA3/9/9/9/3A5/9/4K4 B", [36, 1032, 35410]),
+    ("1D7/9/9/2A6/9/9/4K4/9/9 B", [16, 437, 6423]),
+    ("4K4/9/9/9/3D5/9/9/9/2A6 B", [14, 400, 5165]),
+    ("9/9/2KA4D/9/9/9/9/9/9 W", [20, 255, 6855]),
+    ("9/9/4A4/3AK3A/9/9/9/9/9 B", [30, 271, 10257]),
+    ("9/9/9/4A4/3AKA3/9/9/4A4/9 B", [44, 216, 10100]),
+    ("9/2A3A2/9/9/3DKD3/9/9/2A3A2/9 B", [44, 1284, 56608]),
+    ("3AKA3/8A/9/9/9/9/9/9/9 B", [34, 301, 10995]),
+    ("3AKA3/8A/9/9/9/9/9/9/9 W", [7, 247, 3588]),
+    ("1K7/9/A8/9/9/9/9/9/4A4 B", [27, 419, 9886]),
+];
+
+fn brute_force_perft(board: &mut Board, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    } else if board.outcome().is_some() {
+        return 0;
+    }
+
+    let geometry = board.geometry();
+    let layout = board.layout();
+    let turn_mask = board.turn_mask().unwrap_or_default();
+    let pieces: Vec<_> = board.iter_bitboard().filter(|&(_, s)| layout.mask(s) & turn_mask > Mask(0)).collect();
+    let mut nodes = 0;
+    for (piece, from) in pieces {
+        for to in geometry.squares() {
+            if board.move_piece(Action::new(piece, from, to), None).is_ok() {
+                nodes += brute_force_perft(board, depth - 1);
+                board.undo().unwrap();
+            }
+        }
+    }
+
+    nodes
+}
+
+#[test]
+fn perft_suite_test() -> VikingChessResult<()> {
+    for (fen, expected) in PERFT_SUITE {
+        let mut board = Board::from_fen(fen)?;
+        for (depth, &nodes) in expected.iter().enumerate() {
+            assert_eq!(board.perft(depth + 1, None), nodes, "perft({}) of {fen}", depth + 1);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn perft_brute_force_test() -> VikingChessResult<()> {
+    for (fen, expected) in PERFT_SUITE {
+        let mut board = Board::from_fen(fen)?;
+        for (depth, &nodes) in expected.iter().take(2).enumerate() {
+            assert_eq!(brute_force_perft(&mut board, depth + 1), nodes, "brute force perft({}) of {fen}", depth + 1);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn divide_test() -> VikingChessResult<()> {
+    let mut board = Board::new();
+    let divided = board.divide(2, None);
+
+    assert_eq!(divided.len(), 72);
+    assert_eq!(divided.iter().map(|(_, nodes)| nodes).sum::<u64>(), 3944);
+    assert!(board.divide(0, None).is_empty());
+    Ok(())
+}
+
+#[test]
+fn bitboard_blockers_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    #[rustfmt::skip]
+    const SHIFTS: [u32; Geometry::TABLUT.total_squares()] = [
+        14, 13, 13, 13, 13, 13, 13, 13, 14,
+        13, 12, 12, 12, 12, 12, 12, 12, 13,
+        13, 12, 12, 12, 12, 12, 12, 12, 13,
+        13, 12, 12, 12, 12, 12, 12, 12, 13,
+        13, 12, 12, 12, 12, 12, 12, 12, 13,
+        13, 12, 12, 12, 12, 12, 12, 12, 13,
+        13, 12, 12, 12, 12, 12, 12, 12, 13,
+        13, 12, 12, 12, 12, 12, 12, 12, 13,
+        14, 13, 13, 13, 13, 13, 13, 13, 14,
+    ];
+
+    for (index, shift) in SHIFTS.into_iter().enumerate() {
+        let square = geometry.square(index)?;
+        let blockers: Mask = layout.blockers(square);
+
+        assert_eq!(blockers & !layout.moves(square), Mask(0), "blockers of {square:?}");
+        assert_eq!(blockers.count_ones(), shift, "blockers of {square:?}");
+    }
+
+    assert_eq!(MagicTable::shifts(Geometry::TABLUT.layout()?), SHIFTS);
+    for geometry in Geometry::ALL.into_iter().filter(|geometry| geometry.fits::<Mask>()) {
+        let length = geometry.length() as u32;
+        let layout = geometry.layout::<Mask>()?;
+        let shifts = MagicTable::shifts(layout);
+
+        assert_eq!(shifts[0], 2 * (length - 2));
+        assert_eq!(shifts[1], 2 * (length - 2) - 1);
+        assert_eq!(shifts[geometry.index(geometry.center())], 2 * (length - 3));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn magic_search_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let mut rng = StdRng::seed_from_u64(MagicTable::DEFAULT_SEED);
+
+    for square in [Square::new(0, 0), Square::new(8, 3), Square::new(4, 4)] {
+        let shift = layout.blockers(square).count_ones();
+        let magic = MagicTable::find_magic(Geometry::TABLUT, square, &mut rng)?;
+        let mut moves = HashMap::new();
+
+        let relevant: Mask = layout.blockers(square);
+        let mut blockers = Mask(0);
+        loop {
+            let legal_moves = layout.legal_moves(square, blockers);
+            let index = MagicTable::index(blockers, magic, shift);
+
+            assert!(index < 1 << shift);
+            assert_eq!(*moves.entry(index).or_insert(legal_moves), legal_moves);
+
+            blockers = Mask(blockers.wrapping_sub(relevant.0) & relevant.0);
+            if blockers == Mask(0) {
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn magic_table_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let table = MagicTable::embedded(geometry).expect("Tablut magics are not embedded.");
+    table.verify()?;
+    assert_eq!(&MagicTable::load(MagicTable::MAGICS_PATH)?, table);
+    assert_eq!(&MagicTable::from_magics(table.magics.to_vec())?, table);
+    assert!(matches!(table.moves, Cow::Borrowed(_)));
+
+    let hash_table = HashMagicTable::from(table);
+    let square = Square::new(3, 5);
+    let blockers = Square::new(3, 2).mask(layout) | Square::new(6, 5).mask(layout) | Square::new(0, 0).mask(layout);
+    assert_eq!(table.moves(square, blockers), hash_table.moves(square, blockers));
+    assert_eq!(table.moves(square, blockers), layout.legal_moves(square, blockers & layout.moves(square)));
+    assert_eq!(&MagicTable::try_from(hash_table)?, table);
+
+    let serialized = ron::to_string(table).expect("Failed to serialize the magic table.");
+    assert_eq!(&MagicTable::try_from(serialized).expect("Failed to parse the magic table."), table);
+
+    let corrupt = |change: fn(&mut HashMagicTable)| {
+        let mut hash_table = HashMagicTable::from(table);
+        change(&mut hash_table);
+        MagicTable::try_from(hash_table)
+    };
+
+    assert!(matches!(corrupt(|t| t.magics.truncate(80)), Err(VikingChessError::MagicTableLoad(_))));
+    assert!(matches!(corrupt(|t| t.moves.truncate(80)), Err(VikingChessError::MagicTableLoad(_))));
+    assert!(matches!(corrupt(|t| t.magics[10] = Mask(1)), Err(VikingChessError::MagicTableLoad(_))));
+    assert!(matches!(
+        corrupt(|t| {
+            t.moves[0].insert(Mask(1 << 20), Mask(0));
+        }),
+        Err(VikingChessError::MagicTableLoad(_))
+    ));
+    assert!(matches!(
+        corrupt(|t| t.moves[40].values_mut().for_each(|moves| *moves = Mask(0))),
+        Err(VikingChessError::MagicTableLoad(_))
+    ));
+
+    let serialized = ron::to_string(&HashMagicTable::from(table)).expect("Failed to serialize the hash magic table.");
+    let parsed = HashMagicTable::try_from(serialized.clone()).expect("Failed to parse the hash magic table.");
+    assert_eq!((parsed.geometry, &parsed.blockers[..]), (geometry, &table.blockers[..]));
+    assert_eq!(parsed.moves(square, blockers), table.moves(square, blockers));
+    assert_eq!(&MagicTable::try_from(serialized)?, table);
+    assert!(matches!(MagicTable::try_from(String::from("(magics: [")), Err(VikingChessError::MagicTableLoad(_))));
+
+    for (fen, expected) in PERFT_SUITE.iter().take(4) {
+        let mut board = Board::from_fen(fen)?;
+        assert_eq!(board.perft(3, Some(table)), expected[2], "perft(3) of {fen}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn embedded_magic_tables_test() -> VikingChessResult<()> {
+    let brandubh = MagicTable::embedded(Geometry::BRANDUBH).expect("Brandubh magics are not embedded.");
+    assert_eq!(brandubh.geometry, Geometry::BRANDUBH);
+    assert_eq!(&MagicTable::load(MagicTable::BRANDUBH_MAGICS_PATH)?, brandubh);
+    assert_eq!(&MagicTable::from_magics(brandubh.magics.to_vec())?, brandubh);
+    assert!(matches!(brandubh.moves, Cow::Borrowed(_)));
+    brandubh.verify()?;
+
+    assert_eq!(MagicTable::embedded(Geometry::TABLUT).map(|table| table.geometry), Some(Geometry::TABLUT));
+    assert!(MagicTable::embedded(Geometry::HNEFATAFL).is_none());
+
+    let mut board = Board::from_fen(Board::BRANDUBH_FEN)?;
+    assert_eq!(board.perft(3, MagicTable::embedded(Geometry::BRANDUBH)), board.perft(3, None));
+    Ok(())
+}
+
+#[test]
+fn search_finds_escape_test() -> VikingChessResult<()> {
+    let mut board = Board::from_fen("1K7/9/A8/9/9/9/9/9/4A4 W")?;
+    let result = Search::new(SearchLimits { depth: 3, ..Default::default() }, None).run(&mut board);
+
+    assert_eq!(result.best_action, Some(Action::new(Piece::King, Square::new(0, 1), Square::new(0, 0))));
+    assert_eq!(result.score, MATE_SCORE - 1);
+    assert_eq!(board.to_string(), Board::from_fen("1K7/9/A8/9/9/9/9/9/4A4 W")?.to_string());
+    Ok(())
+}
+
+#[test]
+fn search_finds_king_capture_test() -> VikingChessResult<()> {
+    let mut board = Board::from_fen("9/9/9/4A4/3AKA3/9/9/4A4/9 B")?;
+    let result = Search::new(SearchLimits { depth: 2, ..Default::default() }, MagicTable::embedded(Geometry::TABLUT)).run(&mut board);
+
+    assert_eq!(result.best_action, Some(Action::new(Piece::Attacker, Square::new(7, 4), Square::new(5, 4))));
+    assert_eq!(result.pv.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn search_repetition_test() -> VikingChessResult<()> {
+    let mut board = Board::new();
+    board.set_rules(RuleSet {
+        repetition: Repetition::DefendersWin,
+        ..RuleSet::tablut()
+    });
+
+    let shuffle = [
+        Action::new(Piece::Attacker, Square::new(0, 3), Square::new(0, 2)),
+        Action::new(Piece::Defender, Square::new(2, 4), Square::new(2, 2)),
+        Action::new(Piece::Attacker, Square::new(0, 2), Square::new(0, 3)),
+        Action::new(Piece::Defender, Square::new(2, 2), Square::new(2, 4)),
+    ];
+
+    for action in shuffle.into_iter().cycle().take(2 * shuffle.len() - 1) {
+        board.move_piece(action, None)?;
+    }
+
+    let result = Search::new(SearchLimits { depth: 2, ..Default::default() }, None).run(&mut board);
+    assert_eq!(result.best_action, Some(shuffle[3]));
+    assert_eq!(result.score, MATE_SCORE - 1);
+    Ok(())
+}
+
+#[test]
+fn search_deterministic_test() -> VikingChessResult<()> {
+    let limits = SearchLimits { depth: 3, ..Default::default() };
+    let mut board = Board::new();
+    let first = Search::new(limits, None).run(&mut board);
+    let second = Search::new(limits, MagicTable::embedded(Geometry::TABLUT)).run(&mut board);
+
+    assert_eq!(first, second);
+    assert_eq!(first.depth, 3);
+    assert_eq!(first.pv.len(), 3);
+
+    for &action in &first.pv {
+        board.move_piece(action, None)?;
+    }
+
+    board.undo_n(first.pv.len())?;
+    Ok(())
+}
+
+#[test]
+fn search_limits_test() -> VikingChessResult<()> {
+    let mut board = Board::new();
+
+    let mut search = Search::new(SearchLimits { nodes: Some(500), ..Default::default() }, None);
+    let result = search.run(&mut board);
+    assert!(result.nodes <= 501);
+    assert!(result.best_action.is_some());
+
+    let mut search = Search::new(SearchLimits::default(), None);
+    let stop = search.stop_flag();
+    let stopper = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        stop.store(true, Ordering::Relaxed);
+    });
+    let result = search.run(&mut board);
+    stopper.join().expect("The stopping thread panicked.");
+    assert!(result.best_action.is_some());
+    assert!(result.depth < SearchLimits::MAX_DEPTH);
+
+    let mut search = Search::new(SearchLimits { depth: 2, ..Default::default() }, None);
+    search.stop_flag().store(true, Ordering::Relaxed);
+    let result = search.run(&mut board);
+    assert!(result.depth <= 1 && result.best_action.is_some());
+    assert!(!search.stop_flag().load(Ordering::Relaxed));
+    assert_eq!(search.run(&mut board).depth, 2);
+
+    let limits = SearchLimits { time: Some(Duration::from_millis(50)), ..Default::default() };
+    let start = Instant::now();
+    Search::new(limits, None).run(&mut board);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    Ok(())
+}
+
+#[test]
+fn transposition_store_probe_test() -> VikingChessResult<()> {
+    let table = TranspositionTable::new(1);
+    let entry = TranspositionEntry {
+        depth: 7,
+        score: -MATE_SCORE + 3,
+        bound: Bound::Lower,
+        best_action: Some(Action::new(Piece::King, Square::new(4, 4), Square::new(4, 8))),
+    };
+
+    assert_eq!(table.probe(0xDEAD_BEEF), None);
+    table.store(0xDEAD_BEEF, entry);
+    assert_eq!(table.probe(0xDEAD_BEEF), Some(entry));
+
+    let entry = TranspositionEntry { best_action: None, bound: Bound::Exact, ..entry };
+    table.store(0xDEAD_BEEF, entry);
+    assert_eq!(table.probe(0xDEAD_BEEF), Some(entry));
+
+    let stats = table.stats();
+    assert_eq!((stats.probes, stats.hits, stats.stores), (3, 2, 2));
+    assert!((stats.hit_rate() - 2. / 3.).abs() < f64::EPSILON);
+
+    table.clear();
+    assert_eq!(table.probe(0xDEAD_BEEF), None);
+
+    let entry = TranspositionEntry {
+        best_action: Some(Action::new(Piece::Attacker, Square::new(10, 9), Square::new(0, 9))),
+        ..entry
+    };
+    table.store(0xDEAD_BEEF, entry);
+    assert_eq!(table.probe(0xDEAD_BEEF), Some(entry));
+
+    let entry = TranspositionEntry {
+        score: MATE_SCORE - 1,
+        best_action: Some(Action::new(Piece::Defender, Square::new(18, 17), Square::new(3, 17))),
+        ..entry
+    };
+    table.store(0xDEAD_BEEF, entry);
+    assert_eq!(table.probe(0xDEAD_BEEF), Some(entry));
+    Ok(())
+}
+
+#[test]
+fn transposition_replacement_test() {
+    let table = TranspositionTable::new(0);
+    let entry = |depth| TranspositionEntry { depth, score: depth as i32, bound: Bound::Exact, best_action: None };
+    assert_eq!(table.capacity(), TranspositionTable::BUCKET_SIZE);
+
+    for depth in 1..=4 {
+        table.store(depth as u64, entry(depth));
+    }
+
+    table.store(5, entry(5));
+    assert_eq!(table.probe(1), None);
+    assert!((2..=5).all(|hash| table.probe(hash).is_some()));
+
+    table.store(5, TranspositionEntry { bound: Bound::Upper, ..entry(1) });
+    assert_eq!(table.probe(5), Some(entry(5)));
+
+    table.new_search();
+    table.store(6, entry(1));
+    assert_eq!(table.probe(2), None);
+    assert_eq!(table.probe(6), Some(entry(1)));
+    assert_eq!(table.probe(5), Some(entry(5)));
+}
+
+#[test]
+fn transposition_shared_between_threads_test() {
+    let table = TranspositionTable::new(1);
+
+    std::thread::scope(|scope| {
+        for thread in 0..4u64 {
+            let table = &table;
+            scope.spawn(move || {
+                for i in 0..10_000u64 {
+                    let hash = (i * 4 + thread).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                    let entry = TranspositionEntry { depth: 1, score: hash as u16 as i32, bound: Bound::Exact, best_action: None };
+                    table.store(hash, entry);
+
+                    if let Some(probed) = table.probe(hash) {
+                        assert_eq!(probed.score, hash as u16 as i32);
+                    }
+                }
+            });
+        }
+    });
+
+    assert_eq!(table.stats().stores, 40_000);
+}
+
+#[test]
+fn search_transposition_table_test() -> VikingChessResult<()> {
+    let limits = SearchLimits { depth: 3, ..Default::default() };
+    let table = TranspositionTable::new(16);
+    let mut board = Board::new();
+
+    let without = Search::new(limits, MagicTable::embedded(Geometry::TABLUT)).run(&mut board);
+    let with = Search::new(limits, MagicTable::embedded(Geometry::TABLUT)).with_transposition_table(&table).run(&mut board);
+
+    assert_eq!(with.score, without.score);
+    assert!(with.nodes < without.nodes);
+    assert!(table.stats().hits > 0);
+
+    let mut board = Board::from_fen("1K7/9/A8/9/9/9/9/9/4A4 W")?;
+    let result = Search::new(limits, None).with_transposition_table(&table).run(&mut board);
+    assert_eq!(result.score, MATE_SCORE - 1);
+    Ok(())
+}
+
+#[test]
+fn zobrist_shared_keys_test() -> VikingChessResult<()> {
+    let first = Board::new();
+    let second = Board::from_fen(Board::STARTING_FEN)?;
+    assert_eq!(first.state.zobrist_hash, second.state.zobrist_hash);
+
+    let mut moved = Board::from_fen("9/9/1AD1DA3/9/9/9/3A5/9/4K4 B")?;
+    moved.move_piece(Action::new(Piece::Attacker, Square::new(6, 3), Square::new(2, 3)), None)?;
+    let placed = Board::from_fen("9/9/1A1A1A3/9/9/9/9/9/4K4 W")?;
+    assert_eq!(moved.state.zobrist_hash, placed.state.zobrist_hash);
+
+    assert_eq!(*ZobristTable::new(), **ZobristTable::shared());
+    assert_eq!(*ZobristTable::with_seed(42), *ZobristTable::with_seed(42));
+    assert_ne!(*ZobristTable::with_seed(42), *ZobristTable::new());
+    Ok(())
+}
+
+#[test]
+fn zobrist_turn_and_rules_test() -> VikingChessResult<()> {
+    let attackers = Board::from_fen(Board::STARTING_FEN)?;
+    let defenders = Board::from_fen("3AAA3/4A4/4D4/A3D3A/AADDKDDAA/A3D3A/4D4/4A4/3AAA3 W")?;
+    assert_ne!(attackers.state.zobrist_hash, defenders.state.zobrist_hash);
+
+    let mut board = Board::new();
+    board.set_turn(Piece::Defender);
+    assert_eq!(board.state.zobrist_hash, defenders.state.zobrist_hash);
+    assert_eq!(board.calculate_hash(), board.state.zobrist_hash);
+
+    board.set_king_capture(KingCapture::Custodial);
+    assert_ne!(board.state.zobrist_hash, defenders.state.zobrist_hash);
+    assert_eq!(board.calculate_hash(), board.state.zobrist_hash);
+
+    let mut rng = StdRng::seed_from_u64(0x5EED);
+    for _ in 0..32 {
+        if !random_move(&mut board, &mut rng) {
+            break;
+        }
+
+        assert_eq!(board.calculate_hash(), board.state.zobrist_hash);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn fen_round_trip_test() -> VikingChessResult<()> {
+    for fen in PERFT_SUITE.iter().map(|(fen, _)| *fen).chain([Board::EMPTY_FEN]) {
+        let board: Board = fen.parse()?;
+        assert_eq!(board.to_fen(), fen);
+        assert_eq!(board.to_string(), fen);
+        assert_eq!(Bitboard::from_fen(&board.bitboard().to_fen())?, *board.bitboard());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn fen_round_trip_random_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let mut rng = StdRng::seed_from_u64(0xF3A);
+
+    for _ in 0..256 {
+        let mut bitboard = Bitboard::default();
+        for index in 0..geometry.total_squares() {
+            let mask = geometry.square(index)?.mask(layout);
+            if mask & (layout.corners() | layout.throne()) > Mask(0) {
+                continue;
+            }
+
+            match rng.random_range(0..8) {
+                0 => bitboard[Piece::Attacker] |= mask,
+                1 => bitboard[Piece::Defender] |= mask,
+                2 if bitboard[Piece::King] == Mask(0) => bitboard[Piece::King] |= mask,
+                _ => {}
+            }
+        }
+
+        let fen = bitboard.to_fen();
+        assert_eq!(Bitboard::from_fen(&fen)?, bitboard, "{fen}");
+    }
+
+    let mut board = Board::new();
+    for _ in 0..64 {
+        let parsed: Board = board.to_string().parse()?;
+        assert_eq!(parsed.bitboard(), board.bitboard());
+        assert_eq!(parsed.state.turn, board.state.turn);
+        assert_eq!(parsed.state.zobrist_hash, board.state.zobrist_hash);
+
+        if !random_move(&mut board, &mut rng) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn fen_error_test() {
+    let cases = [
+        ("", FenErrorKind::Empty, 0, 0),
+        ("9/9/9/9/9/9/9/9/9", FenErrorKind::MissingTurn, 9, 0),
+        ("9/9/9/9/9/9/9/9/9 X", FenErrorKind::InvalidTurn, 9, 0),
+        ("9/9/9/9/9/9/9/9/9 B W", FenErrorKind::TrailingInput, 9, 0),
+        ("9/9/9/4X4/9/9/9/9/9 B", FenErrorKind::UnknownCharacter('X'), 3, 4),
+        ("9/9/0A8/9/9/9/9/9/9 B", FenErrorKind::ZeroSkip, 2, 0),
+        ("9/9/9/9/9/9/9/9A/9 B", FenErrorKind::RowTooLong, 7, 9),
+        ("9/9/9/9/9/9/5A4/9/9 B", FenErrorKind::RowTooLong, 6, 6),
+        ("9/9/99999999999999999999/9/9/9/9/9/9 B", FenErrorKind::RowTooLong, 2, 0),
+        ("9/9/A99999999999999999999/9/9/9/9/9/9 B", FenErrorKind::RowTooLong, 2, 1),
+        ("9/9/9/9/9/3A4/9/9/9 B", FenErrorKind::RowTooShort, 5, 8),
+        ("9/9/9/9/9/9/9/9/9/9 B", FenErrorKind::TooManyRows, 9, 0),
+        ("9/9/9/9/9/9/9/9 B", FenErrorKind::TooFewRows, 8, 0),
+        ("8/8/8/8/8/8/8/8 B", FenErrorKind::UnsupportedSize(8), 0, 0),
+        ("7/7/7/7/7/7/7/7 B", FenErrorKind::TooManyRows, 7, 0),
+        ("9/1K7/9/9/9/9/9/6K2/9 B", FenErrorKind::MultipleKings, 7, 6),
+        ("A8/9/9/9/9/9/9/9/9 B", FenErrorKind::RestrictedSquare(Piece::Attacker), 0, 0),
+        ("9/9/9/9/4D4/9/9/9/9 W", FenErrorKind::RestrictedSquare(Piece::Defender), 4, 4),
+    ];
+
+    for (fen, kind, row, col) in cases {
+        match Board::from_fen(fen) {
+            Err(VikingChessError::InvalidFen(error)) => assert_eq!(error, FenError::new(kind, row, col), "{fen:?}"),
+            result => panic!("{fen:?} should not parse, got {:?}", result.err()),
+        }
+    }
+
+    assert!(Board::from_fen("  9/9/9/9/4K4/9/9/9/9   W ").is_ok());
+    assert!(Board::from_fen("8K/9/9/9/9/9/9/9/9 B").is_ok());
+    assert!("9/9/9/9/9/9/9/9 B".parse::<Board>().is_err());
+}
+
+#[test]
+fn fen_garbage_test() {
+    const ALPHABET: [char; 12] = ['A', 'D', 'K', 'X', '/', ' ', 'B', 'W', '0', '1', '9', '\u{e9}'];
+    let mut rng = StdRng::seed_from_u64(0xBAD);
+
+    for _ in 0..4096 {
+        let length = rng.random_range(0..96);
+        let fen: String = (0..length).map(|_| ALPHABET[rng.random_range(0..ALPHABET.len())]).collect();
+        let _ = Board::from_fen(&fen);
+    }
+
+    for _ in 0..1024 {
+        let mut fen = Board::STARTING_FEN.to_string();
+        let index = rng.random_range(0..fen.len());
+        fen.replace_range(index..=index, &ALPHABET[rng.random_range(0..ALPHABET.len())].to_string());
+        let _ = Board::from_fen(&fen);
+    }
+}
+
+#[test]
+fn move_piece_error_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let mut board = Board::new();
+    let attack = |from: Square, to: Square| Action::new(Piece::Attacker, from, to);
+
+    let result = board.move_piece(attack(Square::new(4, 4), Square::new(4, 5)), None);
+    assert!(matches!(result, Err(VikingChessError::NoPieceOnSquare { piece: Piece::Attacker, .. })));
+
+    let result = board.move_piece(Action::new(Piece::Defender, Square::new(2, 4), Square::new(2, 2)), None);
+    assert!(matches!(result, Err(VikingChessError::NotYourTurn { piece: Piece::Defender })));
+
+    let result = board.move_piece(attack(Square::new(0, 3), Square::new(0, 0)), None);
+    assert!(matches!(result, Err(VikingChessError::RestrictedSquare { square, reason: Restriction::KingOnly }) if square == Square::new(0, 0)));
+
+    let result = board.move_piece(attack(Square::new(4, 0), Square::new(4, 4)), None);
+    assert!(matches!(result, Err(VikingChessError::RestrictedSquare { square, reason: Restriction::Throne }) if square == Square::new(4, 4)));
+
+    let mut throne = Board::from_fen("9/9/9/9/5K3/9/9/9/1A7 W")?;
+    throne.set_rules(RuleSet { king_reenters_throne: false, ..RuleSet::copenhagen() });
+    let result = throne.move_piece(Action::new(Piece::King, Square::new(4, 5), Square::new(4, 4)), None);
+    assert!(matches!(result, Err(VikingChessError::RestrictedSquare { reason: Restriction::Throne, .. })));
+    assert_eq!(result.unwrap_err().to_string(), "Square { row: 4, col: 4 } is restricted: the throne can't be re-entered.");
+
+    let result = board.move_piece(attack(Square::new(0, 3), Square::new(0, 7)), None);
+    assert!(matches!(result, Err(VikingChessError::PathBlocked { .. })));
+
+    let result = board.move_piece(attack(Square::new(0, 3), Square::new(2, 2)), None);
+    assert!(matches!(result, Err(VikingChessError::IllegalMove { .. })));
+
+    board.state.turn = Piece::King;
+    assert!(matches!(board.turn_mask(), Err(VikingChessError::InvalidTurn { turn: Piece::King })));
+    assert!(board.legal_actions(None).is_empty());
+
+    let mut board = Board::from_fen("1K7/9/9/9/9/9/9/9/4A4 W")?;
+    board.move_piece(Action::new(Piece::King, Square::new(0, 1), Square::new(0, 0)), None)?;
+    let result = board.move_piece(attack(Square::new(8, 4), Square::new(8, 5)), None);
+    assert!(matches!(result, Err(VikingChessError::GameOver)));
+
+    assert!(matches!(board.undo_n(2), Err(VikingChessError::UndoOutOfRange { requested: 2, available: 1 })));
+    board.undo()?;
+    assert!(matches!(board.undo(), Err(VikingChessError::NothingToUndo)));
+    assert!(matches!(geometry.square(81), Err(VikingChessError::InvalidSquareIndex { index: 81 })));
+    assert!(matches!(Square::try_from((-1., 2.)), Err(VikingChessError::InvalidSquare { col: -1, row: 2 })));
+    assert!(matches!(Piece::try_from('x'), Err(VikingChessError::InvalidPiece('x'))));
+    assert!(matches!(Piece::Length.opposite(), Err(VikingChessError::InvalidPieceKind(Piece::Length))));
+    assert_eq!(Piece::King.opposite()?, Piece::Attacker);
+
+    let mut board = Board::new();
+    let result = board.move_piece(Action::new(Piece::Length, Square::new(0, 3), Square::new(1, 3)), None);
+    assert!(matches!(result, Err(VikingChessError::InvalidPieceKind(Piece::Length))));
+    assert!(!Action::new(Piece::Length, Square::new(0, 3), Square::new(1, 3)).valid(board.bitboard()));
+
+    let mut board = Board13::from_fen(Board::STARTING_FEN)?;
+    let result = board.move_piece(Action::new(Piece::Length, Square::new(0, 3), Square::new(1, 3)), None);
+    assert!(matches!(result, Err(VikingChessError::InvalidPieceKind(Piece::Length))));
+    Ok(())
+}
+
+#[test]
+fn outcome_test() -> VikingChessResult<()> {
+    let win = |winner, reason| Some(GameResult::Win { winner, reason });
+
+    assert_eq!(Board::new().outcome(), None);
+    assert_eq!(Board::from_fen("K8/9/9/9/9/9/9/9/4A4 B")?.outcome(), win(Piece::Defender, WinReason::KingEscaped));
+    assert_eq!(Board::from_fen("9/9/9/9/9/9/9/9/4A4 W")?.outcome(), win(Piece::Attacker, WinReason::KingCaptured));
+
+    let encircled = "9/3AAA3/2A3A2/2A1K1A2/2AD2A2/2A3A2/3AAA3/9/9 B";
+    assert_eq!(Board::from_fen(encircled)?.outcome(), win(Piece::Attacker, WinReason::Encircled));
+    let leaking = "9/3AAA3/2A3A2/2A1K1A2/2AD2A2/2A3A2/3A1A3/9/9 B";
+    assert_eq!(Board::from_fen(leaking)?.outcome(), None);
+    let outside = "9/3AAA3/2A3A2/2A1K1A2/2A3A2/2A3A2/3AAA3/9/1D7 B";
+    assert_eq!(Board::from_fen(outside)?.outcome(), None);
+
+    let magic_table = MagicTable::embedded(Geometry::TABLUT);
+    let mut board = Board::from_fen("9/9/9/9/4K4/9/9/9/9 B")?;
+    assert_eq!(board.outcome(), win(Piece::Defender, WinReason::NoLegalMoves));
+    assert_eq!(board.outcome_with_magic_table(magic_table), win(Piece::Defender, WinReason::NoLegalMoves));
+    board.set_turn(Piece::Defender);
+    assert_eq!(board.outcome(), None);
+    assert_eq!(board.outcome_with_magic_table(magic_table), None);
+
+    let board = Board::from_fen("7/7/7/3K3/7/7/7 W")?;
+    assert_eq!(board.outcome_with_magic_table(magic_table), None);
+
+    let mut board = Board::new();
+    let forth = [
+        Action::new(Piece::Attacker, Square::new(0, 3), Square::new(0, 2)),
+        Action::new(Piece::Defender, Square::new(2, 4), Square::new(2, 2)),
+    ];
+    let back = [
+        Action::new(Piece::Attacker, Square::new(0, 2), Square::new(0, 3)),
+        Action::new(Piece::Defender, Square::new(2, 2), Square::new(2, 4)),
+    ];
+
+    for _ in 0..Board::REPETITION_LIMIT - 1 {
+        assert_eq!(board.outcome(), None);
+        for action in forth.into_iter().chain(back) {
+            board.move_piece(action, None)?;
+        }
+    }
+
+    assert_eq!(board.outcome(), Some(GameResult::Draw { reason: DrawReason::Repetition }));
+    assert!(matches!(board.move_piece(forth[0], None), Err(VikingChessError::GameOver)));
+
+    board.undo()?;
+    board.set_move_limit(Some(board.move_count()));
+    assert_eq!(board.outcome(), Some(GameResult::Draw { reason: DrawReason::MoveLimit }));
+    board.set_move_limit(None);
+    assert_eq!(board.outcome(), None);
+    board.move_piece(back[1], None)?;
+    Ok(())
+}
+
+#[test]
+fn rule_set_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    assert_eq!(RuleSet::default(), RuleSet::tablut());
+    assert_eq!(Board::new().rules(), &RuleSet::tablut());
+    assert_ne!(RuleSet::copenhagen(), RuleSet::fetlar());
+
+    let fen = "4K4/9/9/9/A8/9/9/9/9 B";
+    let mut board = Board::from_fen(fen)?;
+    assert_eq!(board.legal_action_count(None), 13);
+    board.set_rules(RuleSet::fetlar());
+    assert_eq!(board.legal_action_count(None), 9);
+    assert_eq!(board.legal_action_count(MagicTable::embedded(Geometry::TABLUT)), 9);
+    assert_eq!(board.calculate_hash(), board.state.zobrist_hash);
+    assert_ne!(board.state.zobrist_hash, Board::from_fen(fen)?.state.zobrist_hash);
+
+    let fen = "9/9/9/9/6K2/9/9/9/1A7 W";
+    let action = Action::new(Piece::King, Square::new(4, 6), Square::new(4, 4));
+    let mut board = Board::from_fen(fen)?;
+    assert_eq!(board.legal_action_count(None), 15);
+    assert!(matches!(board.move_piece(action, None), Err(VikingChessError::RestrictedSquare { reason: Restriction::Throne, .. })));
+
+    board.set_rules(RuleSet::copenhagen());
+    assert_eq!(board.legal_action_count(None), 16);
+    board.set_rules(RuleSet::fetlar());
+    assert_eq!(board.legal_action_count(None), 12);
+    assert_eq!(board.legal_action_count(MagicTable::embedded(Geometry::TABLUT)), 12);
+    board.move_piece(action, None)?;
+    assert_eq!(board.bitboard()[Piece::King], layout.throne());
+    assert_ne!(RuleSet::tablut().king_reenters_throne, RuleSet::fetlar().king_reenters_throne);
+
+    let fen = "4K4/2A6/9/9/3D5/9/9/9/9 B";
+    let action = Action::new(Piece::Attacker, Square::new(1, 2), Square::new(4, 2));
+    let mut board = Board::from_fen(fen)?;
+    board.move_piece(action, None)?;
+    assert_eq!(board.bitboard()[Piece::Defender], Mask(0));
+
+    let mut board = Board::from_fen(fen)?;
+    board.set_rules(RuleSet::fetlar());
+    board.move_piece(action, None)?;
+    assert_eq!(board.bitboard()[Piece::Defender], Square::new(4, 3).mask(layout));
+
+    let fen = "9/9/2K6/9/2A6/2D6/9/9/9 W";
+    let action = Action::new(Piece::King, Square::new(2, 2), Square::new(3, 2));
+    let mut board = Board::from_fen(fen)?;
+    board.move_piece(action, None)?;
+    assert_eq!(board.bitboard()[Piece::Attacker], Mask(0));
+
+    let mut board = Board::from_fen(fen)?;
+    board.set_rules(RuleSet {
+        armed_king: false,
+        ..RuleSet::tablut()
+    });
+    board.move_piece(action, None)?;
+    assert_eq!(board.bitboard()[Piece::Attacker], Square::new(4, 2).mask(layout));
+
+    let mut board = Board::new();
+    board.set_rules(RuleSet {
+        repetition: Repetition::AttackersWin,
+        ..RuleSet::tablut()
+    });
+
+    let shuffle = [
+        Action::new(Piece::Attacker, Square::new(0, 3), Square::new(0, 2)),
+        Action::new(Piece::Defender, Square::new(2, 4), Square::new(2, 2)),
+        Action::new(Piece::Attacker, Square::new(0, 2), Square::new(0, 3)),
+        Action::new(Piece::Defender, Square::new(2, 2), Square::new(2, 4)),
+    ];
+
+    for action in shuffle.into_iter().cycle().take(shuffle.len() * (Board::REPETITION_LIMIT - 1)) {
+        board.move_piece(action, None)?;
+    }
+
+    let win = GameResult::Win {
+        winner: Piece::Attacker,
+        reason: WinReason::Repetition,
+    };
+    assert_eq!(board.outcome(), Some(win));
+    Ok(())
+}
+
+#[test]
+fn edge_escape_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let rules = RuleSet::linnaeus();
+    let mut board = Board::from_fen_with_rules(Board::STARTING_FEN, rules)?;
+    assert_eq!(board.perft(1, None), 80);
+    assert!(board.legal_actions(None).iter().any(|a| a.to.mask(layout) & layout.corners() > Mask(0)));
+    assert_eq!(board.perft(2, None), brute_force_perft(&mut board, 2));
+    assert_eq!(board.perft(3, None), board.perft(3, MagicTable::embedded(Geometry::TABLUT)));
+
+    assert!(Board::from_fen("8A/9/9/9/4K4/9/9/9/9 W").is_err());
+    assert!(Board::from_fen_with_rules("8A/9/9/9/4K4/9/9/9/9 W", rules).is_ok());
+    assert!(Board::from_fen_with_rules("9/9/9/9/4D4/9/9/9/4K4 W", rules).is_err());
+
+    let escape = Action::new(Piece::King, Square::new(4, 4), Square::new(4, 0));
+    let mut board = Board::from_fen_with_rules("4A4/9/9/9/4K4/9/9/9/9 W", rules)?;
+    board.move_piece(escape, None)?;
+    let win = GameResult::Win {
+        winner: Piece::Defender,
+        reason: WinReason::KingEscaped,
+    };
+    assert_eq!(board.outcome(), Some(win));
+
+    let mut board = Board::from_fen("4A4/9/9/9/4K4/9/9/9/9 W")?;
+    board.move_piece(escape, None)?;
+    assert_eq!(board.outcome(), None);
+
+    let capture = Action::new(Piece::Attacker, Square::new(3, 2), Square::new(0, 2));
+    let mut board = Board::from_fen("1D7/9/9/2A6/9/9/4K4/9/9 B")?;
+    board.move_piece(capture, None)?;
+    assert_eq!(board.bitboard()[Piece::Defender], Mask(0));
+
+    let mut board = Board::from_fen_with_rules("1D7/9/9/2A6/9/9/4K4/9/9 B", rules)?;
+    board.move_piece(capture, None)?;
+    assert_eq!(board.bitboard()[Piece::Defender], Square::new(0, 1).mask(layout));
+
+    let mut board = Board::from_fen_with_rules("4A4/9/9/9/4K4/9/9/9/9 W", rules)?;
+    let result = Search::new(SearchLimits { depth: 2, ..Default::default() }, None).run(&mut board);
+    assert_eq!(result.score, MATE_SCORE - 1);
+    assert!(result.best_action.is_some_and(|a| a.to.mask(layout) & layout.edges() > Mask(0)));
+    Ok(())
+}
+
+#[test]
+fn shieldwall_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let rules = RuleSet::copenhagen();
+    let close = Action::new(Piece::Attacker, Square::new(5, 5), Square::new(8, 5));
+    let wall = Square::new(8, 3).mask(layout) | Square::new(8, 4).mask(layout);
+
+    let mut board = Board::from_fen_with_rules("9/9/4K4/9/9/5A3/9/3AA4/2ADD4 B", rules)?;
+    board.move_piece(close, None)?;
+    assert_eq!(board.bitboard()[Piece::Defender], Mask(0));
+    assert_eq!(board.state.captured[Piece::Defender], wall);
+    board.undo()?;
+    assert_eq!(board.bitboard()[Piece::Defender], wall);
+
+    let mut board = Board::from_fen("9/9/4K4/9/9/5A3/9/3AA4/2ADD4 B")?;
+    board.move_piece(close, None)?;
+    assert_eq!(board.bitboard()[Piece::Defender], wall);
+
+    let mut board = Board::from_fen_with_rules("9/9/4K4/9/9/5A3/9/3A5/2ADD4 B", rules)?;
+    board.move_piece(close, None)?;
+    assert_eq!(board.bitboard()[Piece::Defender], wall);
+
+    let mut board = Board::from_fen_with_rules("9/9/9/9/9/5A3/9/3AA4/2ADK4 B", rules)?;
+    board.move_piece(close, None)?;
+    assert_eq!(board.bitboard()[Piece::Defender], Mask(0));
+    assert_eq!(board.bitboard()[Piece::King], Square::new(8, 4).mask(layout));
+
+    let mut board = Board::from_fen_with_rules("9/9/4K4/9/9/3A5/9/1AA6/1DD6 B", rules)?;
+    board.move_piece(Action::new(Piece::Attacker, Square::new(5, 3), Square::new(8, 3)), None)?;
+    assert_eq!(board.bitboard()[Piece::Defender], Mask(0));
+
+    let mut board = Board::from_fen_with_rules("9/9/4K4/9/9/3A5/9/1AA6/1DD6 B", rules)?;
+    board.move_piece(Action::new(Piece::Attacker, Square::new(5, 3), Square::new(5, 0)), None)?;
+    assert_eq!(board.bitboard()[Piece::Defender], Square::new(8, 1).mask(layout) | Square::new(8, 2).mask(layout));
+
+    let mut board = Board::from_fen_with_rules("9/9/4K4/9/9/9/9/1AA6/1DDA3A1 B", rules)?;
+    board.move_piece(Action::new(Piece::Attacker, Square::new(8, 7), Square::new(8, 5)), None)?;
+    assert_eq!(board.bitboard()[Piece::Defender], Square::new(8, 1).mask(layout) | Square::new(8, 2).mask(layout));
+
+    let mut board = Board::from_fen_with_rules("6A2/9/3D5/AD7/AD2K4/D8/9/9/9 W", rules)?;
+    board.move_piece(Action::new(Piece::Defender, Square::new(2, 3), Square::new(2, 0)), None)?;
+    assert_eq!(board.bitboard()[Piece::Attacker], Square::new(0, 6).mask(layout));
+    Ok(())
+}
+
+#[test]
+fn edge_fort_test() -> VikingChessResult<()> {
+    let rules = RuleSet::copenhagen();
+    let fort = GameResult::Win {
+        winner: Piece::Defender,
+        reason: WinReason::EdgeFort,
+    };
+
+    let fen = "3A5/9/9/9/9/9/3DDD3/3D1D3/3DKD3 B";
+    assert_eq!(Board::from_fen_with_rules(fen, rules)?.outcome(), Some(fort));
+    assert_eq!(Board::from_fen(fen)?.outcome(), None);
+
+    let fen = "3A5/9/9/9/9/9/3DD4/3D1D3/3DKD3 B";
+    assert_eq!(Board::from_fen_with_rules(fen, rules)?.outcome(), Some(fort));
+
+    let fen = "3A5/9/9/9/9/9/4D4/3D1D3/3DKD3 B";
+    assert_eq!(Board::from_fen_with_rules(fen, rules)?.outcome(), None);
+
+    let fen = "3A5/9/9/9/9/9/3DDD3/3DDD3/3DKD3 B";
+    assert_eq!(Board::from_fen_with_rules(fen, rules)?.outcome(), None);
+
+    let fen = "3A5/9/9/9/9/9/3DDD3/3D5/3DKD3 B";
+    assert_eq!(Board::from_fen_with_rules(fen, rules)?.outcome(), None);
+
+    let fen = "3A5/9/9/9/9/9/3DDD3/3DAD3/3DKD3 B";
+    assert_eq!(Board::from_fen_with_rules(fen, rules)?.outcome(), None);
+
+    let mut board = Board::from_fen_with_rules("3A5/9/9/9/9/3D5/4D4/3D1D3/3DKD3 W", rules)?;
+    assert_eq!(board.outcome(), None);
+    board.move_piece(Action::new(Piece::Defender, Square::new(5, 3), Square::new(6, 3)), None)?;
+    assert_eq!(board.outcome(), Some(fort));
+    assert!(matches!(board.move_piece(Action::new(Piece::Attacker, Square::new(0, 3), Square::new(0, 2)), None), Err(VikingChessError::GameOver)));
+    Ok(())
+}
+
+#[test]
+fn geometry_test() -> VikingChessResult<()> {
+    let tablut = Geometry::TABLUT;
+    let layout = tablut.layout::<Mask>()?;
+    assert_eq!(Geometry::default(), tablut);
+    assert_eq!(layout.corners(), Mask(1 | 1 << 8 | 1 << 72 | 1 << 80));
+    assert_eq!(layout.throne(), layout.mask(tablut.center()));
+    assert_eq!(layout.edges().count_ones(), 32);
+    assert!(matches!(Geometry::new(8), Err(VikingChessError::UnsupportedBoardSize { length: 8 })));
+    assert!(matches!(Geometry::new(15), Err(VikingChessError::UnsupportedBoardSize { length: 15 })));
+    assert!(matches!(Geometry::TABLUT_13.layout::<Mask>(), Err(VikingChessError::BoardTooLarge { length: 13, bits: 128 })));
+    let wide = Geometry::TABLUT_13.layout::<WideMask<3>>()?;
+    assert_eq!(Square::new(12, 12).mask(wide), WideMask::bit(168));
+    assert_eq!(Square::new(0, 12).adjacent_mask(wide), WideMask::bit(11) | WideMask::bit(25));
+    assert!(std::ptr::eq(Geometry::ALEA_EVANGELII.layout::<Mask19>()?, Geometry::ALEA_EVANGELII.layout::<Mask19>()?));
+    assert_eq!(Geometry::ALEA_EVANGELII.layout::<Mask19>()?.board().count_ones() as usize, Geometry::ALEA_EVANGELII.total_squares());
+
+    for geometry in Geometry::ALL.into_iter().filter(|geometry| geometry.fits::<Mask>()) {
+        let layout = geometry.layout::<Mask>()?;
+        let length = geometry.length();
+        assert_eq!(Geometry::new(length)?, geometry);
+        assert_eq!(layout.board().count_ones() as usize, geometry.total_squares());
+        assert_eq!(layout.edges().count_ones() as usize, 4 * (length - 1));
+        assert_eq!(layout.corners() & !layout.edges(), Mask(0));
+        assert_eq!(layout.throne(), layout.mask(geometry.center()));
+        assert!(geometry.square(geometry.total_squares()).is_err());
+
+        for (index, square) in geometry.squares().enumerate() {
+            assert_eq!(geometry.index(square), index);
+            assert_eq!(layout.moves(square).count_ones() as usize, 2 * (length - 1));
+            assert_eq!(layout.legal_moves(square, Mask(0)), layout.moves(square));
+            assert_eq!(layout.blockers(square) & !layout.moves(square), Mask(0));
+            assert_eq!(layout.spread(layout.mask(square)), layout.adjacent(square));
+        }
+    }
+
+    for geometry in Geometry::ALL {
+        let length = geometry.length();
+        let layout = Layout::<Mask19>::new(geometry);
+        assert_eq!(Geometry::new(length)?, geometry);
+        assert_eq!(layout.board().count_ones() as usize, geometry.total_squares());
+        assert_eq!(layout.edges().count_ones() as usize, 4 * (length - 1));
+        assert_eq!(layout.throne(), layout.mask(geometry.center()));
+
+        for square in geometry.squares() {
+            assert_eq!(layout.legal_moves(square, Mask19::EMPTY).count_ones() as usize, 2 * (length - 1));
+            assert_eq!(layout.spread(layout.mask(square)), layout.adjacent(square));
+            assert_eq!(layout.transform(layout.mask(square), Symmetry::Rotate90), layout.mask(geometry.transform_square(square, Symmetry::Rotate90)));
+        }
+    }
+
+    Ok(())
+}
+
+fn step(geometry: Geometry, square: Square, direction: Direction, distance: i8) -> Option<Square> {
+    let (row_step, col_step) = direction.offset();
+    let row = square.row as i8 + row_step * distance;
+    let col = square.col as i8 + col_step * distance;
+    let length = geometry.length() as i8;
+
+    match (0..length).contains(&row) && (0..length).contains(&col) {
+        true => Some(Square::new(row as u8, col as u8)),
+        false => None,
+    }
+}
+
+#[test]
+fn mask_shift_and_ray_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let center = Square::try_from((4, 4))?;
+    assert_eq!(center.mask(layout).north(layout), Square::try_from((4, 3))?.mask(layout));
+    assert_eq!(center.mask(layout).east(layout), Square::try_from((5, 4))?.mask(layout));
+    assert_eq!(center.mask(layout).south(layout), Square::try_from((4, 5))?.mask(layout));
+    assert_eq!(center.mask(layout).west(layout), Square::try_from((3, 4))?.mask(layout));
+    assert_eq!(Mask::file(8, layout).east(layout), Mask(0));
+    assert_eq!(Mask::file(0, layout).west(layout), Mask(0));
+    assert_eq!(Mask::rank(0, layout).north(layout), Mask(0));
+    assert_eq!(Mask::rank(8, layout).south(layout), Mask(0));
+    assert_eq!(Mask::file(3, layout).east(layout), Mask::file(4, layout));
+    assert_eq!(Mask::rank(3, layout).south(layout), Mask::rank(4, layout));
+    assert_eq!(Mask::ray(Square::new(0, 0), Direction::East, layout), Mask::rank(0, layout) & !Mask(1));
+    assert_eq!(Mask::ray(Square::new(0, 0), Direction::North, layout), Mask(0));
+
+    for geometry in Geometry::ALL.into_iter().filter(|geometry| geometry.fits::<Mask>()) {
+        let layout = geometry.layout::<Mask>()?;
+        for square in geometry.squares() {
+            let mut cross = Mask(0);
+            for direction in Direction::ALL {
+                let expected = step(geometry, square, direction, 1).map_or(Mask(0), |target| layout.mask(target));
+                assert_eq!(layout.shift(layout.mask(square), direction), expected, "{square:?} {direction:?}");
+
+                let ray = (1..geometry.length() as i8)
+                    .filter_map(|distance| step(geometry, square, direction, distance))
+                    .fold(Mask(0), |mask, target| mask | layout.mask(target));
+                assert_eq!(layout.ray(square, direction), ray, "{square:?} {direction:?}");
+                assert_eq!(layout.ray(square, direction) & layout.ray(square, direction.opposite()), Mask(0));
+                cross |= ray;
+            }
+
+            assert_eq!(cross, layout.moves(square));
+            assert_eq!(layout.rank(square.row) & layout.file(square.col), layout.mask(square));
+        }
+    }
+
+    let mut rng = StdRng::seed_from_u64(24);
+    for geometry in Geometry::ALL.into_iter().filter(|geometry| geometry.fits::<Mask>()) {
+        let layout = geometry.layout::<Mask>()?;
+        for _ in 0..100 {
+            let mask = Mask(rng.random::<u128>()) & layout.board();
+            for direction in Direction::ALL {
+                let expected = mask
+                    .iter_squares(geometry)
+                    .fold(Mask(0), |shifted, square| shifted | layout.shift(layout.mask(square), direction));
+                assert_eq!(layout.shift(mask, direction), expected);
+            }
+
+            assert_eq!(
+                Direction::ALL.into_iter().fold(Mask(0), |spread, direction| spread | layout.shift(mask, direction)),
+                layout.spread(mask)
+            );
+        }
+    }
+
+    for _ in 0..100 {
+        let mask = Mask(rng.random::<u128>());
+        assert_eq!(mask.north(layout), layout.shift(mask, Direction::North));
+        assert_eq!(mask.east(layout), layout.shift(mask, Direction::East));
+        assert_eq!(mask.south(layout), layout.shift(mask, Direction::South));
+        assert_eq!(mask.west(layout), layout.shift(mask, Direction::West));
+        assert_eq!(mask.north(layout) & !layout.board(), Mask(0));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn mask_between_and_line_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let a = Square::try_from((1, 4))?;
+    let b = Square::try_from((6, 4))?;
+    let expected = (2..6).map(|col| Square::new(4, col).mask(layout)).fold(Mask(0), |a, b| a | b);
+    assert_eq!(Mask::between(a, b, layout), expected);
+    assert_eq!(Mask::between(b, a, layout), expected);
+    assert_eq!(Mask::line(a, b, layout), Mask::rank(4, layout));
+    assert_eq!(Mask::between(a, Square::new(5, 5), layout), Mask(0));
+    assert_eq!(Mask::line(a, Square::new(5, 5), layout), Mask(0));
+    assert_eq!(Mask::between(a, a, layout), Mask(0));
+    assert_eq!(Mask::line(a, a, layout), Mask(0));
+    assert_eq!(Mask::between(a, Square::new(4, 2), layout), Mask(0));
+
+    for geometry in Geometry::ALL.into_iter().filter(|geometry| geometry.fits::<Mask>()) {
+        let layout = geometry.layout::<Mask>()?;
+        for a in geometry.squares() {
+            for b in geometry.squares() {
+                let aligned = (a.row == b.row) != (a.col == b.col);
+                let expected = match aligned {
+                    true => geometry
+                        .squares()
+                        .filter(|s| match a.row == b.row {
+                            true => s.row == a.row && s.col > a.col.min(b.col) && s.col < a.col.max(b.col),
+                            false => s.col == a.col && s.row > a.row.min(b.row) && s.row < a.row.max(b.row),
+                        })
+                        .fold(Mask(0), |mask, s| mask | layout.mask(s)),
+                    false => Mask(0),
+                };
+
+                let between: Mask = layout.between(a, b);
+                let line: Mask = layout.line(a, b);
+                assert_eq!(between, expected, "{a:?} {b:?}");
+                assert_eq!(between, layout.between(b, a));
+                assert_eq!(line, layout.line(b, a));
+                let expected_line = match aligned {
+                    true => geometry.length(),
+                    false => 0,
+                };
+
+                assert_eq!(line.count() as usize, expected_line);
+                assert_eq!(between & !line, Mask(0));
+
+                if aligned {
+                    assert!(line & layout.mask(a) > Mask(0) && line & layout.mask(b) > Mask(0));
+                    assert_eq!(layout.legal_moves(a, layout.mask(b)) & layout.legal_moves(b, layout.mask(a)), between);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn mask_flood_fill_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let wall = Mask::file(4, layout);
+    let left = Mask::file(0, layout) | Mask::file(1, layout) | Mask::file(2, layout) | Mask::file(3, layout);
+    assert_eq!(Mask::flood_fill(Square::new(0, 0).mask(layout), !wall, layout), left);
+    assert_eq!(Mask::flood_fill(Square::new(0, 0).mask(layout), layout.board(), layout), layout.board());
+    assert_eq!(Mask::flood_fill(Square::new(0, 4).mask(layout), !wall, layout), Mask(0));
+    assert_eq!(Mask::flood_fill(Mask(0), !wall, layout), Mask(0));
+
+    let mut rng = StdRng::seed_from_u64(24);
+    for geometry in Geometry::ALL.into_iter().filter(|geometry| geometry.fits::<Mask>()) {
+        let layout = geometry.layout::<Mask>()?;
+        for _ in 0..200 {
+            let passable = Mask(rng.random::<u128>() | rng.random::<u128>()) & layout.board();
+            let seed = Mask(rng.random::<u128>() & rng.random::<u128>() & rng.random::<u128>()) & layout.board();
+
+            let mut filled = seed & passable;
+            let mut frontier: Vec<Square> = filled.iter_squares(geometry).collect();
+            while let Some(square) = frontier.pop() {
+                for direction in Direction::ALL {
+                    let Some(next) = step(geometry, square, direction, 1) else {
+                        continue;
+                    };
+
+                    if layout.mask(next) & passable & !filled > Mask(0) {
+                        filled |= layout.mask(next);
+                        frontier.push(next);
+                    }
+                }
+            }
+
+            assert_eq!(layout.flood_fill(seed, passable), filled);
+            if geometry == Geometry::TABLUT {
+                assert_eq!(Mask::flood_fill(seed, passable, layout), filled);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn mask_symmetry_test() -> VikingChessResult<()> {
+    let geometry = Geometry::TABLUT;
+    let layout = geometry.layout::<Mask>()?;
+    let square = Square::try_from((2, 1))?;
+    assert_eq!(square.mask(layout).flip_vertical(layout), Square::try_from((2, 7))?.mask(layout));
+    assert_eq!(square.mask(layout).flip_horizontal(layout), Square::try_from((6, 1))?.mask(layout));
+    assert_eq!(square.mask(layout).transpose(layout), Square::try_from((1, 2))?.mask(layout));
+    assert_eq!(square.mask(layout).rotate90(layout), Square::try_from((7, 2))?.mask(layout));
+    assert_eq!(square.transformed(Symmetry::Rotate90, geometry), Square::try_from((7, 2))?);
+    assert_eq!(layout.corners().rotate90(layout), layout.corners());
+    assert_eq!(layout.edges().transpose(layout), layout.edges());
+    assert_eq!(Mask::rank(2, layout).transpose(layout), Mask::file(2, layout));
+    assert_eq!(Mask::file(2, layout).rotate90(layout), Mask::rank(2, layout));
+
+    let mut rng = StdRng::seed_from_u64(25);
+    for geometry in Geometry::ALL.into_iter().filter(|geometry| geometry.fits::<Mask>()) {
+        let layout = geometry.layout::<Mask>()?;
+        for symmetry in Symmetry::ALL {
+            let inverse = symmetry.inverse();
+            assert_eq!(inverse.inverse(), symmetry);
+
+            let mut image = Mask(0);
+            for square in geometry.squares() {
+                let transformed = geometry.transform_square(square, symmetry);
+                assert!(geometry.contains(transformed));
+                assert_eq!(geometry.transform_square(transformed, inverse), square);
+                assert_eq!(layout.transform(layout.mask(square), symmetry), layout.mask(transformed));
+                image |= layout.mask(transformed);
+            }
+
+            assert_eq!(image, layout.board());
+            assert_eq!(layout.transform(layout.corners(), symmetry), layout.corners());
+            assert_eq!(layout.transform(layout.throne(), symmetry), layout.throne());
+            assert_eq!(layout.transform(layout.edges(), symmetry), layout.edges());
+
+            for _ in 0..20 {
+                let mask = Mask(rng.random::<u128>()) & layout.board();
+                let transformed = layout.transform(mask, symmetry);
+                assert_eq!(transformed.count(), mask.count());
+                assert_eq!(layout.transform(transformed, inverse), mask);
+                assert_eq!(mask.transformed(symmetry, layout), transformed);
+            }
+        }
+
+        for _ in 0..20 {
+            let mask = Mask(rng.random::<u128>()) & layout.board();
+            let rotated = (0..4).fold(mask, |rotated, _| layout.rotate90(rotated));
+            assert_eq!(rotated, mask);
+            assert_eq!(layout.flip_vertical(layout.flip_vertical(mask)), mask);
+            assert_eq!(layout.flip_horizontal(layout.flip_horizontal(mask)), mask);
+            assert_eq!(layout.transpose(layout.transpose(mask)), mask);
+            assert_eq!(layout.transform(mask, Symmetry::Rotate90), layout.rotate90(mask));
+            assert_eq!(
+                layout.transform(mask, Symmetry::Rotate270),
+                layout.rotate90(layout.rotate90(layout.rotate90(mask)))
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn board_symmetry_test() -> VikingChessResult<()> {
+    let board = Board::new();
+    for symmetry in Symmetry::ALL {
+        let transformed = board.transformed(symmetry);
+        assert_eq!(transformed.to_fen(), Board::STARTING_FEN);
+        assert_eq!(transformed.state.zobrist_hash, board.state.zobrist_hash);
+    }
+
+    assert_eq!(board.canonical().1, Symmetry::Identity);
+
+    let mut board = Board::from_fen("3AAA3/4A4/4D4/A3D3A/AADDK1DAA/A3D3A/4D4/4A4/3AA4 W")?;
+    let transformed = board.transformed(Symmetry::Rotate90);
+    assert_eq!(transformed.to_fen(), "3AAA3/4A4/4D4/A3D3A/AADDKDDAA/8A/4D4/4A4/3AAA3 W");
+    assert_eq!(transformed.rules(), board.rules());
+    assert_eq!(transformed.move_count(), 0);
+
+    let fens = PERFT_SUITE.iter().map(|(fen, _)| *fen).chain([Board::BRANDUBH_FEN, Board::HNEFATAFL_FEN]);
+    for fen in fens {
+        let board = Board::from_fen(fen)?;
+        let geometry = board.geometry();
+        let (canonical, symmetry) = board.canonical();
+        assert_eq!(canonical.transformed(symmetry.inverse()).to_fen(), board.to_fen(), "{fen}");
+
+        let mut actions: Vec<_> = board.legal_actions(None).iter().map(|a| a.transformed(symmetry, geometry)).collect();
+        let mut expected: Vec<_> = canonical.legal_actions(None).to_vec();
+        actions.sort_by_key(|a| (a.from.row, a.from.col, a.to.row, a.to.col));
+        expected.sort_by_key(|a| (a.from.row, a.from.col, a.to.row, a.to.col));
+        assert_eq!(actions, expected, "{fen}");
+
+        for symmetry in Symmetry::ALL {
+            let mut transformed = board.transformed(symmetry);
+            let reloaded = Board::from_fen_with_rules(&transformed.to_fen(), *board.rules())?;
+            assert_eq!(transformed.state.zobrist_hash, reloaded.state.zobrist_hash, "{fen}");
+            assert_eq!(transformed.outcome(), board.outcome(), "{fen}");
+            assert_eq!(transformed.canonical().0.state.zobrist_hash, canonical.state.zobrist_hash, "{fen}");
+            assert_eq!(transformed.legal_action_count(None), board.legal_action_count(None), "{fen}");
+            assert_eq!(transformed.perft(2, None), Board::from_fen(fen)?.perft(2, None), "{fen}");
+        }
+    }
+
+    let geometry = board.geometry();
+    for action in board.legal_actions(None).iter().copied() {
+        let mut transformed = board.transformed(Symmetry::Transpose);
+        board.move_piece(action, None)?;
+        transformed.move_piece(action.transformed(Symmetry::Transpose, geometry), None)?;
+
+        let expected = board.transformed(Symmetry::Transpose);
+        assert_eq!(transformed.to_fen(), expected.to_fen());
+        assert_eq!(transformed.state.zobrist_hash, expected.state.zobrist_hash);
+        board.undo()?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn geometry_starting_positions_test() -> VikingChessResult<()> {
+    let positions = [
+        (Board::BRANDUBH_FEN, Geometry::BRANDUBH, [8, 4]),
+        (Board::TABLUT_FEN, Geometry::TABLUT, [16, 8]),
+        (Board::TAWLBWRDD_FEN, Geometry::HNEFATAFL, [24, 12]),
+        (Board::HNEFATAFL_FEN, Geometry::HNEFATAFL, [24, 12]),
+    ];
+
+    for (fen, geometry, [attackers, defenders]) in positions {
+        let board = Board::from_fen(fen)?;
+        assert_eq!(board.geometry(), geometry, "{fen}");
+        assert_eq!(board.to_fen(), fen);
+        assert_eq!(board.bitboard()[Piece::Attacker].count_ones(), attackers, "{fen}");
+        assert_eq!(board.bitboard()[Piece::Defender].count_ones(), defenders, "{fen}");
+        assert_eq!(board.bitboard()[Piece::King], board.layout().throne(), "{fen}");
+        assert_eq!(board.outcome(), None, "{fen}");
+    }
+
+    let mut board = Board::from_fen(Board::BRANDUBH_FEN)?;
+    assert_eq!(board.perft(1, None), 40);
+    assert_eq!(board.perft(2, None), brute_force_perft(&mut board, 2));
+
+    let mut board = Board::from_fen(Board::HNEFATAFL_FEN)?;
+    assert_eq!(board.perft(1, None), 116);
+    assert_eq!(board.perft(2, None), brute_force_perft(&mut board, 2));
+    Ok(())
+}
+
+#[test]
+fn geometry_magic_table_test() -> VikingChessResult<()> {
+    let table = MagicTable::generate_with_geometry(Geometry::BRANDUBH, MagicTable::DEFAULT_SEED)?;
+    assert_eq!(table.geometry, Geometry::BRANDUBH);
+    assert_eq!(MagicTable::from_magics(table.magics.to_vec())?, table);
+    assert!(matches!(MagicTable::from_magics(vec![Mask(0); 50]), Err(VikingChessError::MagicTableLoad(_))));
+
+    let mut board = Board::from_fen(Board::BRANDUBH_FEN)?;
+    let expected = board.perft(3, None);
+    assert_eq!(board.perft(3, Some(&table)), expected);
+    assert_eq!(board.perft(3, MagicTable::embedded(Geometry::TABLUT)), expected);
+
+    let mut board = Board::from_fen("1K9/11/A10/11/11/11/11/11/11/11/5A5 W")?;
+    let result = Search::new(SearchLimits { depth: 2, ..Default::default() }, MagicTable::embedded(Geometry::TABLUT)).run(&mut board);
+    assert_eq!(result.best_action, Some(Action::new(Piece::King, Square::new(0, 1), Square::new(0, 0))));
+    Ok(())
+}
+
+#[test]
+fn wide_mask_test() {
+    let mut rng = StdRng::seed_from_u64(0x19);
+
+    for _ in 0..256 {
+        let (a, b) = (rng.random::<u128>(), rng.random::<u128>());
+        let (wide_a, wide_b) = (WideMask::<2>::from(Mask(a)), WideMask::<2>::from(Mask(b)));
+        let shift = rng.random_range(0..128);
+
+        assert_eq!(wide_a | wide_b, WideMask::from(Mask(a | b)));
+        assert_eq!(wide_a & wide_b, WideMask::from(Mask(a & b)));
+        assert_eq!(!wide_a, WideMask::from(Mask(!a)));
+        assert_eq!(wide_a << shift, WideMask::from(Mask(a << shift)));
+        assert_eq!(wide_a >> shift, WideMask::from(Mask(a >> shift)));
+        assert_eq!(wide_a.count_ones(), a.count_ones());
+        assert_eq!(wide_a.trailing_zeros(), a.trailing_zeros());
+        assert_eq!(ron::to_string(&wide_a).ok(), ron::to_string(&Mask(a)).ok());
+    }
+
+    let mut mask = WideMask::<6>::bit(360) | WideMask::bit(64) | WideMask::bit(3);
+    assert_eq!((mask.pop_lsb(), mask.pop_lsb(), mask.pop_lsb(), mask.pop_lsb()), (Some(3), Some(64), Some(360), None));
+    assert!(mask.is_empty());
+    assert_eq!(mask.trailing_zeros(), 384);
+    assert_eq!(WideMask::<6>::bit(300) << 84, WideMask::EMPTY);
+    assert_eq!(WideMask::<6>::bit(300) >> 237, WideMask::bit(63));
+
+    let geometry = Geometry::TABLUT_13;
+    assert!(geometry.fits::<WideMask<3>>() && Geometry::ALEA_EVANGELII.fits::<Mask19>());
+    assert!(!geometry.fits::<Mask>() && !Geometry::ALEA_EVANGELII.fits::<WideMask<3>>());
+    assert_eq!(WideMask::<3>::bit(14).to_string(), WideMask::<3>::bit(14).display(geometry).to_string());
+    assert_eq!(WideMask::<3>::bit(14).to_string().lines().nth(1), Some(".1..........."));
+
+    let squares = [0, 10, 40, 44, 80];
+    let mask = squares.into_iter().fold(Mask::EMPTY, |a, index| a | Mask::bit(index));
+    let wide = squares.into_iter().fold(WideMask::<3>::EMPTY, |a, index| a | WideMask::bit(index));
+    assert_eq!(wide.display(Geometry::TABLUT).to_string(), mask.to_string());
+    assert_eq!((WideMask::<3>::bit(192), WideMask::<3>::bit(192).get(192)), (WideMask::EMPTY, false));
+
+    let mask = (0..40).fold(Mask19::EMPTY, |a, _| a | Mask19::bit(rng.random_range(0..361)));
+    let serialized = ron::to_string(&mask).expect("Failed to serialize the mask.");
+    assert_eq!(ron::from_str::<Mask19>(&serialized).ok(), Some(mask));
+    assert_eq!(ron::from_str::<Mask19>("\"12345678901234567890123456789\"").ok().map(|mask| mask.0[1]), Some(669260594));
+    assert!(ron::from_str::<WideMask<1>>("\"18446744073709551616\"").is_err());
+    assert!(ron::from_str::<WideMask<1>>("18446744073709551616").is_err());
+}
+
+#[test]
+fn wide_board_matches_board_test() -> VikingChessResult<()> {
+    let rule_sets = [RuleSet::tablut(), RuleSet::copenhagen(), RuleSet::fetlar(), RuleSet::linnaeus()];
+    let fens = PERFT_SUITE.iter().map(|(fen, _)| *fen).chain([Board::BRANDUBH_FEN]);
+
+    for fen in fens {
+        for rules in rule_sets {
+            let mut board = Board::from_fen_with_rules(fen, rules)?;
+            let mut wide = WideBoard::<2>::from_fen_with_rules(fen, rules)?;
+            assert_eq!(wide.to_fen(), board.to_fen());
+
+            for depth in 1..=2 {
+                assert_eq!(wide.perft(depth, None), board.perft(depth, None), "perft({depth}) of {fen} with {rules:?}");
+            }
+        }
+    }
+
+    let mut board = Board::from_fen("9/9/9/4A4/3AKA3/9/9/4A4/9 B")?;
+    let mut wide = WideBoard::<2>::from_fen("9/9/9/4A4/3AKA3/9/9/4A4/9 B")?;
+    for king_capture in [KingCapture::Surrounded, KingCapture::SurroundedOnEdge, KingCapture::Custodial] {
+        board.set_king_capture(king_capture);
+        wide.set_king_capture(king_capture);
+        assert_eq!(wide.perft(3, None), board.perft(3, None), "perft(3) with {king_capture:?}");
+    }
+
+    let mut wide = WideBoard::<2>::from_fen(Board::HNEFATAFL_FEN)?;
+    assert_eq!(wide.perft(2, None), Board::from_fen(Board::HNEFATAFL_FEN)?.perft(2, None));
+    Ok(())
+}
+
+#[test]
+fn wide_board_test() -> VikingChessResult<()> {
+    let fen = "3AAAAAAA3/6A6/13/A5D5A/A5D5A/A5D5A/AADDDDKDDDDAA/A5D5A/A5D5A/A5D5A/13/6A6/3AAAAAAA3 B";
+    let mut board = Board13::from_fen(fen)?;
+    assert_eq!((board.geometry().length(), board.to_fen()), (13, fen.to_string()));
+    assert_eq!(board.bitboard()[Piece::Attacker].count_ones(), 32);
+    assert_eq!(board.perft(1, None), board.legal_actions(None).len() as u64);
+
+    let actions = board.legal_actions(None);
+    board.move_piece(actions[0], None)?;
+    assert_eq!(board.calculate_hash(), board.state.zobrist_hash);
+    assert_eq!(board.undo()?, actions[0]);
+    assert_eq!(board.to_fen(), fen);
+
+    let mut board = Board19::from_fen("19/19/19/19/19/19/19/19/19/19/19/19/19/9D9/9A9/9K9/19/19/19 B")?;
+    assert_eq!(board.geometry(), Geometry::ALEA_EVANGELII);
+    let crowded = Board19::from_fen("19/19/19/AAAAAAAAAAAAAAAAAAA/19/19/19/19/19/9K9/19/19/19/19/19/AAAAAAAAAAAAAAAAAAA/19/19/19 B")?;
+    assert_eq!(crowded.legal_actions(None).len(), crowded.legal_action_count(None));
+    assert!(crowded.legal_action_count(None) > ActionList::<512>::CAPACITY);
+    board.move_piece(Action::new(Piece::Attacker, Square::new(14, 9), Square::new(14, 0)), None)?;
+    board.move_piece(Action::new(Piece::Defender, Square::new(13, 9), Square::new(13, 0)), None)?;
+    board.move_piece(Action::new(Piece::Attacker, Square::new(14, 0), Square::new(14, 18)), None)?;
+    board.move_piece(Action::new(Piece::King, Square::new(15, 9), Square::new(15, 18)), None)?;
+    assert_eq!(board.outcome(), None);
+    board.move_piece(Action::new(Piece::Attacker, Square::new(14, 18), Square::new(14, 17)), None)?;
+    board.move_piece(Action::new(Piece::King, Square::new(15, 18), Square::new(18, 18)), None)?;
+    assert_eq!(board.outcome(), Some(GameResult::Win { winner: Piece::Defender, reason: WinReason::KingEscaped }));
+    assert!(matches!(board.move_piece(Action::new(Piece::Attacker, Square::new(14, 17), Square::new(14, 0)), None), Err(VikingChessError::GameOver)));
+
+    let mut board = Board19::from_fen("19/19/19/19/19/19/19/19/19/19/19/19/19/19/3A15/3D15/19/3A15/9K9 B")?;
+    board.move_piece(Action::new(Piece::Attacker, Square::new(17, 3), Square::new(16, 3)), None)?;
+    assert_eq!(board.bitboard()[Piece::Defender], WideMask::EMPTY);
+    assert_eq!(board.undo()?.to, Square::new(16, 3));
+    assert_eq!(board.bitboard()[Piece::Defender], WideMask::bit(15 * 19 + 3));
+
+    let result = board.move_piece(Action::new(Piece::Attacker, Square::new(14, 3), Square::new(14, 19)), None);
+    assert!(matches!(result, Err(VikingChessError::InvalidSquare { col: 19, row: 14 })));
+    assert!(matches!(Board13::from_fen(Board::STARTING_FEN), Ok(board) if board.geometry() == Geometry::TABLUT));
+
+    let mut board = Board13::from_fen_with_rules(fen, RuleSet::copenhagen())?;
+    board.set_move_limit(Some(1));
+    board.move_piece(actions[0], None)?;
+    assert_eq!(board.outcome(), Some(GameResult::Draw { reason: DrawReason::MoveLimit }));
+
+    match Board13::from_fen("19/19/19/19/19/19/19/19/19/19/19/19/19/19/19/19/19/19/19 B") {
+        Err(VikingChessError::InvalidFen(error)) => assert_eq!(error.kind, FenErrorKind::UnsupportedSize(19)),
+        result => panic!("19x19 should not fit in Board13, got {:?}", result.err()),
+    }
+
+    Ok(())
+}
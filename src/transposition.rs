@@ -0,0 +1,234 @@
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::Ordering;
+
+use crate::action::Action;
+use crate::geometry::Geometry;
+use crate::piece::Piece;
+use crate::square::Square;
+
+#[repr(u8)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Bound {
+    Exact = 1,
+    Lower = 2,
+    Upper = 3,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TranspositionEntry {
+    pub depth: u8,
+    pub score: i32,
+    pub bound: Bound,
+    pub best_action: Option<Action>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct TranspositionStats {
+    pub probes: u64,
+    pub hits: u64,
+    pub stores: u64,
+}
+
+impl TranspositionStats {
+    pub fn hit_rate(&self) -> f64 {
+        match self.probes {
+            0 => 0.,
+            probes => self.hits as f64 / probes as f64,
+        }
+    }
+}
+
+pub struct TranspositionTable {
+    slots: Vec<AtomicU64>,
+    buckets: usize,
+    age: AtomicU8,
+    probes: AtomicU64,
+    hits: AtomicU64,
+    stores: AtomicU64,
+}
+
+impl TranspositionTable {
+    pub const BUCKET_SIZE: usize = 4;
+    const SLOTS_PER_BUCKET: usize = Self::BUCKET_SIZE * 2;
+    const BUCKET_BYTES: usize = Self::SLOTS_PER_BUCKET * size_of::<u64>();
+    const AGE_MASK: u8 = 0x3f;
+
+    pub fn new(megabytes: usize) -> Self {
+        let buckets = (megabytes * 1024 * 1024 / Self::BUCKET_BYTES).max(1);
+
+        Self {
+            slots: (0..buckets * Self::SLOTS_PER_BUCKET).map(|_| AtomicU64::new(0)).collect(),
+            buckets,
+            age: AtomicU8::new(0),
+            probes: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            stores: AtomicU64::new(0),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buckets * Self::BUCKET_SIZE
+    }
+
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            slot.store(0, Ordering::Relaxed);
+        }
+
+        self.age.store(0, Ordering::Relaxed);
+        self.probes.store(0, Ordering::Relaxed);
+        self.hits.store(0, Ordering::Relaxed);
+        self.stores.store(0, Ordering::Relaxed);
+    }
+
+    pub fn new_search(&self) {
+        let age = self.age.load(Ordering::Relaxed);
+        self.age.store((age + 1) & Self::AGE_MASK, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> TranspositionStats {
+        TranspositionStats {
+            probes: self.probes.load(Ordering::Relaxed),
+            hits: self.hits.load(Ordering::Relaxed),
+            stores: self.stores.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn hashfull(&self) -> usize {
+        let sample = self.capacity().min(1000);
+        let age = self.age.load(Ordering::Relaxed);
+        let used = (0..sample)
+            .map(|i| self.slots[i * 2 + 1].load(Ordering::Relaxed))
+            .filter(|&data| bound(data).is_some() && entry_age(data) == age)
+            .count();
+
+        used * 1000 / sample
+    }
+
+    pub fn probe(&self, hash: u64) -> Option<TranspositionEntry> {
+        self.probes.fetch_add(1, Ordering::Relaxed);
+
+        let bucket = self.bucket(hash);
+        for i in 0..Self::BUCKET_SIZE {
+            let key = self.slots[bucket + i * 2].load(Ordering::Relaxed);
+            let data = self.slots[bucket + i * 2 + 1].load(Ordering::Relaxed);
+
+            if key ^ data == hash && let Some(entry) = decode(data) {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Some(entry);
+            }
+        }
+
+        None
+    }
+
+    pub fn store(&self, hash: u64, entry: TranspositionEntry) {
+        let bucket = self.bucket(hash);
+        let age = self.age.load(Ordering::Relaxed);
+        let mut victim = 0;
+        let mut victim_worth = i32::MAX;
+
+        for i in 0..Self::BUCKET_SIZE {
+            let key = self.slots[bucket + i * 2].load(Ordering::Relaxed);
+            let data = self.slots[bucket + i * 2 + 1].load(Ordering::Relaxed);
+
+            if bound(data).is_none() {
+                victim = i;
+                break;
+            } else if key ^ data == hash {
+                let stored = decode(data);
+                if entry.bound != Bound::Exact && stored.is_some_and(|s| s.depth > entry.depth.saturating_add(2)) {
+                    return;
+                }
+
+                victim = i;
+                break;
+            }
+
+            let stale = (age.wrapping_sub(entry_age(data)) & Self::AGE_MASK) as i32;
+            let worth = depth(data) as i32 - 8 * stale;
+            if worth < victim_worth {
+                victim = i;
+                victim_worth = worth;
+            }
+        }
+
+        let data = encode(entry, age);
+        self.slots[bucket + victim * 2].store(hash ^ data, Ordering::Relaxed);
+        self.slots[bucket + victim * 2 + 1].store(data, Ordering::Relaxed);
+        self.stores.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn bucket(&self, hash: u64) -> usize {
+        (((hash as u128) * (self.buckets as u128)) >> 64) as usize * Self::SLOTS_PER_BUCKET
+    }
+}
+
+const SCORE_BITS: u32 = 24;
+const COORDINATE_BITS: u32 = u32::BITS - (Geometry::MAX_LENGTH as u32 - 1).leading_zeros();
+const COORDINATE_MASK: u64 = (1 << COORDINATE_BITS) - 1;
+const PIECE_SHIFT: u32 = 4 * COORDINATE_BITS;
+
+fn encode(entry: TranspositionEntry, age: u8) -> u64 {
+    debug_assert!(entry.score.unsigned_abs() < 1 << (SCORE_BITS - 1), "score {} does not fit the entry.", entry.score);
+
+    let action = match entry.best_action {
+        Some(action) => {
+            let coordinates = [action.from.row, action.from.col, action.to.row, action.to.col];
+            let squares = coordinates.iter().enumerate().fold(0, |squares, (i, &coordinate)| {
+                squares | (coordinate as u64) << (i as u32 * COORDINATE_BITS)
+            });
+
+            squares | (action.piece as u64) << PIECE_SHIFT
+        }
+        None => 3 << PIECE_SHIFT,
+    };
+
+    (entry.score as u32 as u64 & ((1 << SCORE_BITS) - 1))
+        | (entry.depth as u64) << 24
+        | (entry.bound as u64) << 32
+        | (age as u64) << 34
+        | action << 40
+}
+
+fn decode(data: u64) -> Option<TranspositionEntry> {
+    let action = data >> 40;
+    let piece = match (action >> PIECE_SHIFT) & 3 {
+        0 => Some(Piece::King),
+        1 => Some(Piece::Defender),
+        2 => Some(Piece::Attacker),
+        _ => None,
+    };
+
+    let coordinate = |i: u32| ((action >> (i * COORDINATE_BITS)) & COORDINATE_MASK) as u8;
+    let best_action = piece.map(|piece| {
+        let from = Square::new(coordinate(0), coordinate(1));
+        let to = Square::new(coordinate(2), coordinate(3));
+        Action::new(piece, from, to)
+    });
+
+    Some(TranspositionEntry {
+        depth: depth(data),
+        score: ((data << (u64::BITS - SCORE_BITS)) as i64 >> (u64::BITS - SCORE_BITS)) as i32,
+        bound: bound(data)?,
+        best_action,
+    })
+}
+
+fn depth(data: u64) -> u8 {
+    (data >> 24) as u8
+}
+
+fn bound(data: u64) -> Option<Bound> {
+    match (data >> 32) & 3 {
+        1 => Some(Bound::Exact),
+        2 => Some(Bound::Lower),
+        3 => Some(Bound::Upper),
+        _ => None,
+    }
+}
+
+fn entry_age(data: u64) -> u8 {
+    ((data >> 34) & TranspositionTable::AGE_MASK as u64) as u8
+}
@@ -0,0 +1,302 @@
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::ops::BitAnd;
+use std::ops::BitAndAssign;
+use std::ops::BitOr;
+use std::ops::BitOrAssign;
+use std::ops::Not;
+use std::ops::Shl;
+use std::ops::Shr;
+
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use serde::de;
+
+use crate::bit_set::BitSet;
+use crate::geometry::Geometry;
+use crate::mask::Mask;
+
+pub type Mask13 = WideMask<3>;
+pub type Mask19 = WideMask<6>;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct WideMask<const N: usize>(pub [u64; N]);
+
+impl<const N: usize> WideMask<N> {
+    pub fn count_ones(&self) -> u32 {
+        self.0.iter().map(|word| word.count_ones()).sum()
+    }
+
+    pub fn trailing_zeros(&self) -> u32 {
+        match self.0.iter().position(|&word| word != 0) {
+            Some(i) => i as u32 * u64::BITS + self.0[i].trailing_zeros(),
+            None => Self::BITS as u32,
+        }
+    }
+
+    fn to_decimal(self) -> String {
+        const CHUNK: u128 = 10_000_000_000_000_000_000;
+        let mut words = self.0;
+        let mut chunks = Vec::new();
+
+        loop {
+            let mut remainder = 0u128;
+            for word in words.iter_mut().rev() {
+                let current = remainder << 64 | *word as u128;
+                *word = (current / CHUNK) as u64;
+                remainder = current % CHUNK;
+            }
+
+            chunks.push(remainder as u64);
+            if words.iter().all(|&word| word == 0) {
+                break;
+            }
+        }
+
+        let mut decimal = chunks.pop().unwrap_or_default().to_string();
+        for chunk in chunks.iter().rev() {
+            decimal += &format!("{chunk:019}");
+        }
+
+        decimal
+    }
+
+    fn from_decimal(value: &str) -> Option<Self> {
+        let mut words = [0u64; N];
+        if value.is_empty() {
+            return None;
+        }
+
+        for ch in value.chars() {
+            let mut carry = ch.to_digit(10)? as u128;
+            for word in words.iter_mut() {
+                let current = *word as u128 * 10 + carry;
+                *word = current as u64;
+                carry = current >> 64;
+            }
+
+            if carry > 0 {
+                return None;
+            }
+        }
+
+        Some(Self(words))
+    }
+}
+
+impl<const N: usize> BitSet for WideMask<N> {
+    const BITS: usize = N * u64::BITS as usize;
+    const EMPTY: WideMask<N> = WideMask([0; N]);
+
+    fn bit(index: usize) -> Self {
+        let mut mask = Self::EMPTY;
+        if index < Self::BITS {
+            mask.0[index / 64] = 1 << (index % 64);
+        }
+
+        mask
+    }
+
+    fn low_bits(count: usize) -> Self {
+        let mut mask = Self::EMPTY;
+        for (i, word) in mask.0.iter_mut().enumerate() {
+            *word = match count.saturating_sub(i * 64) {
+                0 => 0,
+                bits if bits >= 64 => u64::MAX,
+                bits => (1 << bits) - 1,
+            };
+        }
+
+        mask
+    }
+
+    fn get(self, index: usize) -> bool {
+        index < Self::BITS && (self.0[index / 64] >> (index % 64)) & 1 == 1
+    }
+
+    fn shift_left(self, bits: usize) -> Self {
+        self << bits
+    }
+
+    fn shift_right(self, bits: usize) -> Self {
+        self >> bits
+    }
+
+    fn is_empty(self) -> bool {
+        self.0.iter().all(|&word| word == 0)
+    }
+
+    fn count(self) -> u32 {
+        self.count_ones()
+    }
+
+    fn lsb(self) -> Option<usize> {
+        let i = self.0.iter().position(|&word| word != 0)?;
+        Some(i * 64 + self.0[i].trailing_zeros() as usize)
+    }
+
+    fn pop_lsb(&mut self) -> Option<usize> {
+        let index = self.lsb()?;
+        self.0[index / 64] &= self.0[index / 64] - 1;
+        Some(index)
+    }
+}
+
+impl<const N: usize> Default for WideMask<N> {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl<const N: usize> From<Mask> for WideMask<N> {
+    fn from(value: Mask) -> Self {
+        let mut mask = Self::EMPTY;
+        for (i, word) in mask.0.iter_mut().enumerate().take(2) {
+            *word = (value.0 >> (i * 64)) as u64;
+        }
+
+        mask
+    }
+}
+
+impl<const N: usize> BitOr for WideMask<N> {
+    type Output = Self;
+
+    fn bitor(mut self, rhs: Self) -> Self::Output {
+        self |= rhs;
+        self
+    }
+}
+
+impl<const N: usize> BitOrAssign for WideMask<N> {
+    fn bitor_assign(&mut self, rhs: Self) {
+        for (word, other) in self.0.iter_mut().zip(rhs.0) {
+            *word |= other;
+        }
+    }
+}
+
+impl<const N: usize> BitAnd for WideMask<N> {
+    type Output = Self;
+
+    fn bitand(mut self, rhs: Self) -> Self::Output {
+        self &= rhs;
+        self
+    }
+}
+
+impl<const N: usize> BitAndAssign for WideMask<N> {
+    fn bitand_assign(&mut self, rhs: Self) {
+        for (word, other) in self.0.iter_mut().zip(rhs.0) {
+            *word &= other;
+        }
+    }
+}
+
+impl<const N: usize> Not for WideMask<N> {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self(self.0.map(|word| !word))
+    }
+}
+
+impl<const N: usize> Shl<usize> for WideMask<N> {
+    type Output = Self;
+
+    fn shl(self, rhs: usize) -> Self::Output {
+        let (words, bits) = (rhs / 64, rhs % 64);
+        let mut shifted = Self::EMPTY;
+
+        for i in words..N {
+            shifted.0[i] = self.0[i - words] << bits;
+            if bits > 0 && i > words {
+                shifted.0[i] |= self.0[i - words - 1] >> (64 - bits);
+            }
+        }
+
+        shifted
+    }
+}
+
+impl<const N: usize> Shr<usize> for WideMask<N> {
+    type Output = Self;
+
+    fn shr(self, rhs: usize) -> Self::Output {
+        let (words, bits) = (rhs / 64, rhs % 64);
+        let mut shifted = Self::EMPTY;
+
+        for i in 0..N.saturating_sub(words) {
+            shifted.0[i] = self.0[i + words] >> bits;
+            if bits > 0 && i + words + 1 < N {
+                shifted.0[i] |= self.0[i + words + 1] << (64 - bits);
+            }
+        }
+
+        shifted
+    }
+}
+
+impl<const N: usize> Display for WideMask<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match Geometry::ALL.into_iter().rev().find(|geometry| geometry.fits::<Self>()) {
+            Some(geometry) => write!(f, "{}", self.display(geometry)),
+            None => write!(f, "{}", self.to_decimal()),
+        }
+    }
+}
+
+impl<const N: usize> Serialize for WideMask<N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_decimal())
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for WideMask<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct WideMaskVisitor<const N: usize>;
+
+        impl<'de, const N: usize> de::Visitor<'de> for WideMaskVisitor<N> {
+            type Value = WideMask<N>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "an unsigned {}-bit integer that can be used as a WideMask", N * 64)
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                self.visit_u128(value as u128)
+            }
+
+            fn visit_u128<E>(self, value: u128) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                match N >= 2 || value >> (N * 64) == 0 {
+                    true => Ok(WideMask::from(Mask(value))),
+                    false => Err(E::custom(format!("{value} does not fit in {} bits", N * 64))),
+                }
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                WideMask::from_decimal(value).ok_or_else(|| E::custom(format!("{value:?} is not an unsigned {}-bit integer", N * 64)))
+            }
+        }
+
+        deserializer.deserialize_any(WideMaskVisitor)
+    }
+}
@@ -1,52 +1,114 @@
 use std::ops::Deref;
-use std::ops::Index;
-use std::ops::IndexMut;
 
-use rand::RngCore;
-use rand::rng;
-
-use crate::bitboard::Bitboard;
+use crate::geometry::Geometry;
 use crate::piece::Piece;
+use crate::rules::Escape;
+use crate::rules::KingCapture;
+use crate::rules::Repetition;
+use crate::rules::RuleSet;
 use crate::square::Square;
 
+static SHARED_TABLE: ZobristTable = ZobristTable::with_seed(ZobristTable::DEFAULT_SEED);
+
 pub struct ZobristTable([u64; ZobristTable::TABLE_LENGTH]);
 
 impl ZobristTable {
-    pub const TABLE_LENGTH: usize = Bitboard::TOTAL_SQUARES * Piece::Length as usize;
+    pub const PIECE_KEYS: usize = Geometry::MAX_SQUARES * Piece::Length as usize;
+    pub const TURN_KEYS: usize = Piece::Length as usize;
+    pub const KING_CAPTURE_KEYS: usize = 3;
+    pub const ESCAPE_KEYS: usize = 2;
+    pub const REPETITION_KEYS: usize = 3;
+    pub const RULE_FLAG_KEYS: usize = 6;
+    pub const RULE_KEYS: usize = Self::KING_CAPTURE_KEYS + Self::ESCAPE_KEYS + Self::REPETITION_KEYS + Self::RULE_FLAG_KEYS;
+    pub const GEOMETRY_KEYS: usize = Geometry::ALL.len();
+    pub const TABLE_LENGTH: usize = Self::PIECE_KEYS + Self::TURN_KEYS + Self::RULE_KEYS + Self::GEOMETRY_KEYS;
+    const RULES_OFFSET: usize = Self::PIECE_KEYS + Self::TURN_KEYS;
+    const GEOMETRY_OFFSET: usize = Self::RULES_OFFSET + Self::RULE_KEYS;
+    pub const DEFAULT_SEED: u64 = 0x5EED_7AF1_0000_0001;
 
     pub fn new() -> Self {
+        Self::with_seed(Self::DEFAULT_SEED)
+    }
+
+    pub fn shared() -> &'static ZobristTable {
+        &SHARED_TABLE
+    }
+
+    pub const fn with_seed(seed: u64) -> Self {
         let mut keys = [0u64; Self::TABLE_LENGTH];
-        let mut r = rng();
-        for key in keys.iter_mut().take(Self::TABLE_LENGTH) {
-            *key = r.next_u64();
+        let mut state = seed;
+        let mut i = 0;
+
+        while i < Self::TABLE_LENGTH {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            keys[i] = z ^ (z >> 31);
+            i += 1;
         }
 
         Self(keys)
     }
-}
 
-impl Deref for ZobristTable {
-    type Target = [u64; ZobristTable::TABLE_LENGTH];
+    pub fn piece_key(&self, geometry: Geometry, piece: Piece, square: Square) -> u64 {
+        self.index_key(piece, geometry.index(square), geometry.total_squares())
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    pub fn index_key(&self, piece: Piece, index: usize, total_squares: usize) -> u64 {
+        self.0[piece as usize * total_squares + index]
+    }
+
+    pub fn geometry_key(&self, geometry: Geometry) -> u64 {
+        self.0[Self::GEOMETRY_OFFSET + geometry.id()]
+    }
+
+    pub fn turn_key(&self, turn: Piece) -> u64 {
+        self.0[Self::PIECE_KEYS + turn as usize]
+    }
+
+    pub fn king_capture_key(&self, king_capture: KingCapture) -> u64 {
+        self.0[Self::RULES_OFFSET + king_capture as usize]
+    }
+
+    pub fn escape_key(&self, escape: Escape) -> u64 {
+        self.0[Self::RULES_OFFSET + Self::KING_CAPTURE_KEYS + escape as usize]
+    }
+
+    pub fn repetition_key(&self, repetition: Repetition) -> u64 {
+        self.0[Self::RULES_OFFSET + Self::KING_CAPTURE_KEYS + Self::ESCAPE_KEYS + repetition as usize]
+    }
+
+    pub fn rules_key(&self, rules: &RuleSet) -> u64 {
+        let flags = [
+            rules.throne_passable,
+            rules.throne_hostile_to_defenders,
+            rules.armed_king,
+            rules.shieldwall,
+            rules.edge_fort,
+            rules.king_reenters_throne,
+        ];
+
+        let offset = Self::RULES_OFFSET + Self::RULE_KEYS - Self::RULE_FLAG_KEYS;
+        let mut key = self.king_capture_key(rules.king_capture) ^ self.escape_key(rules.escape) ^ self.repetition_key(rules.repetition);
+        for (i, _) in flags.iter().enumerate().filter(|(_, enabled)| **enabled) {
+            key ^= self.0[offset + i];
+        }
+
+        key
     }
 }
 
-impl IndexMut<(Piece, Square)> for ZobristTable {
-    fn index_mut(&mut self, index: (Piece, Square)) -> &mut Self::Output {
-        let piece = index.0 as usize;
-        let square = index.1.row as usize * Bitboard::BOARD_LENGTH + index.1.col as usize;
-        &mut self.0[piece * Bitboard::TOTAL_SQUARES + square]
+impl Default for ZobristTable {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-impl Index<(Piece, Square)> for ZobristTable {
-    type Output = u64;
+impl Deref for ZobristTable {
+    type Target = [u64; ZobristTable::TABLE_LENGTH];
 
-    fn index(&self, index: (Piece, Square)) -> &Self::Output {
-        let piece = index.0 as usize;
-        let square = index.1.row as usize * Bitboard::BOARD_LENGTH + index.1.col as usize;
-        &self.0[piece * Bitboard::TOTAL_SQUARES + square]
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
 }